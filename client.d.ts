@@ -6,6 +6,29 @@ export interface PlayerSteamId {
   steamId32: string
   accountId: number
 }
+/**
+ * Returned by the `watch*` functions and `log.subscribe`. Call `stop()` to end the background thread or subscription.
+ * Polling intervals (`intervalMs`) below 100 ms are raised to 100 ms.
+ */
+export class WatchHandle {
+  stop(): void
+  get isStopped(): boolean
+}
+export interface ExeFilters {
+  /** Case-insensitive glob patterns matched against file names, excluded from matching */
+  deny: Array<string>
+  /** Patterns that are always kept and ranked first, overriding `deny` */
+  allow: Array<string>
+}
+export interface RankedExe {
+  path: string
+  name: string
+  score: number
+  /** Why the score is what it is, e.g. "matches install dir name (+40)" */
+  reasons: Array<string>
+  /** Excluded exes are still listed, but never used for process matching */
+  excluded: boolean
+}
 export namespace achievement {
   export function isActivated(achievement: string): boolean
   export function unlock(achievement: string): boolean
@@ -51,8 +74,75 @@ export namespace localplayer {
   export function setRichPresence(key: string, value?: string | undefined | null): void
 }
 export namespace log {
-  export function initLogger(appData: string): string
-  export function testPanic(): void
+  export interface LogOptions {
+    /** Rotate "rust.log" once it grows past this many bytes, 0 to only rotate by age. Defaults to 10 MiB. */
+    maxSize?: number
+    /** Also rotate once the current file has been written to for this many hours */
+    maxAgeHours?: number
+    /** Rotated files to keep, "rust.log.1" being the newest. Defaults to 5. */
+    maxFiles?: number
+    /** Keep writing to the previous session's "rust.log" after a session header, instead of rotating it out */
+    append?: boolean
+    /**
+     * "text" (the default) or "json" for one JSON object per line with timestamp, level, target, message,
+     * file, line and thread
+     */
+    format?: string
+  }
+  export interface LogFilter {
+    /** Module path the level applies to, along with everything below it, e.g. "steamworksjs::api::processes" */
+    target: string
+    level: string
+  }
+  export interface LogConfig {
+    /** Level for targets without a filter of their own */
+    level: string
+    filters: Array<LogFilter>
+  }
+  export interface LogRecord {
+    /** Milliseconds since the Unix epoch */
+    time: number
+    level: string
+    target: string
+    message: string
+  }
+  export interface CrashReport {
+    /** The report file, to attach to bug reports */
+    path: string
+    /** RFC 3339 local time of the panic */
+    time: string
+    /** Version of this module */
+    version: string
+    os: string
+    thread: string
+    location: string
+    message: string
+    appId?: number
+    steamInitialised: boolean
+    backtrace: string
+    /** The last log lines before the panic, oldest first */
+    recentLog: Array<string>
+  }
+  export function initLogger(appData: string, options?: LogOptions | undefined | null): string
+  /** Sets the level for targets without a filter of their own. Takes effect immediately. */
+  export function setLevel(level: string): void
+  /** Sets the level for `target` and the modules below it, e.g. `setFilter("steamworksjs::api::processes","warn")` */
+  export function setFilter(target: string, level: string): void
+  /** Returns whether there was a filter for `target` */
+  export function removeFilter(target: string): boolean
+  export function getConfig(): LogConfig
+  /**
+   * Calls `callback` with every record at `minLevel` or above (all by default) that passes `setLevel` and
+   * `setFilter`, once `initLogger` has run. Records are queued for JS, so logging never waits on the callback.
+   */
+  export function subscribe(callback: (record: LogRecord) => void, minLevel?: string | undefined | null): WatchHandle
+  /** The newest crash report in "<appData>/crashes", from this session or an earlier one */
+  export function getLastCrashReport(): CrashReport | null
+  /**
+   * Panics on the calling thread. With `onThread`, panics on a "test-panic" thread instead and returns
+   * the crash report it produced, so the report can be checked without taking the app down.
+   */
+  export function testPanic(onThread?: boolean | undefined | null): CrashReport | null
 }
 export namespace screenshots {
   export function addScreenshotToLibrary(filename: string, width: number, height: number): number
@@ -65,11 +155,295 @@ export namespace utils {
   export function uiLanguage(): string
 }
 export namespace processes {
+  /** Every executable under `install_dir`, best game candidate first, with the reasons for each score */
+  export function rankInstallDirExes(installDir: string): Array<RankedExe>
+  export function rankGameExes(appid: number): Array<RankedExe>
+  /**
+   * Indexes the app's install dir on a background thread, so the first process lookup doesn't have to walk it.
+   * The index is reused until the app is updated or its dirs change.
+   */
+  export function prebuildExeIndex(appid: number): void
+  /** Where exe indexes are persisted. Defaults to "steamworksjs/exeindex" under the user's cache dir ("~/.cache" or "%LOCALAPPDATA%"). */
+  export function setExeIndexDir(dir: string): void
+  /** Removes the index for `appid`, or every index if omitted */
+  export function clearExeIndex(appid?: number | undefined | null): void
+  export function getExeFilters(): ExeFilters
+  /** Replaces the deny/allow lists used when ranking exes. Extend the result of `getExeFilters()` to keep the defaults. */
+  export function setExeFilters(filters: ExeFilters): void
+  export function resetExeFilters(): void
   export interface ProcessInfo {
     pid: number
     exe: string
+    /** Windows exe name for games running under Wine/Proton, e.g. "Game.exe" */
+    windowsExe?: string
+    /** Linux path of the executable. For Wine/Proton games this is the resolved ".exe", not the Wine loader */
+    linuxPath?: string
+  }
+  /**
+   * Finds the game's processes from its install dir. `linkedgame` replaces that lookup with one or more entries,
+   * each an exe name, a full path, a glob (any entry with `*` or `?`, e.g. "Dolphin*.exe") or a "re:" prefixed regex.
+   * Paths and globs are matched against the process' exe path and argv[0], so launch wrappers aren't; regexes also
+   * see the command line.
+   */
+  export function getGameProcesses(appid: number, linkedgame?: string | Array<string> | undefined | null): Array<ProcessInfo>
+  /**
+   * Every process Steam launched for `appid` (launchers, the game and their children), found via the
+   * `SteamAppId` environment variable. Other processes' environments can't be read on Windows, so it
+   * falls back to matching the install dir's exe names there.
+   */
+  export function getAppProcesses(appid: number): Array<ProcessInfo>
+  export interface ProcessNode {
+    pid: number
+    ppid: number
+    name: string
+    exe: string
+    /** 0 for processes whose parent is outside the tree */
+    depth: number
+  }
+  export interface ProcessTreeChange {
+    /** "join" or "leave" */
+    kind: string
+    process: ProcessNode
+  }
+  /** `pid` and all of its descendants */
+  export function getProcessTree(pid: number): Array<ProcessNode>
+  /** All processes launched for `appid` and their descendants */
+  export function getAppProcessTree(appid: number): Array<ProcessNode>
+  /**
+   * Calls `handler` as processes join or leave the tree under `pid`. Members are kept until they exit,
+   * even if reparented, and the watcher stops once the whole tree has exited.
+   */
+  export function watchProcessTree(pid: number, handler: (change: ProcessTreeChange) => void, intervalMs?: number | undefined | null): WatchHandle
+  /**
+   * Like `watchProcessTree`, but rooted at every process launched for `appid`, so a game restarting itself
+   * or being relaunched is picked up. Runs until stopped.
+   */
+  export function watchAppProcessTree(appid: number, handler: (change: ProcessTreeChange) => void, intervalMs?: number | undefined | null): WatchHandle
+  export interface ProcessExit {
+    pid: number
+    /** Milliseconds since the Unix epoch, usable with `new Date()` */
+    exitTime: number
+    /** How the exit was detected: "pidfd", "handle", "poll", or "none" if the process had already exited */
+    method: string
+  }
+  /**
+   * Calls `handler` exactly once when `pid` exits, without polling where the platform supports it.
+   * Calls back straight away if the process isn't running.
+   */
+  export function watchExit(pid: number, handler: (exit: ProcessExit) => void): WatchHandle
+  export interface ProcessStats {
+    pid: number
+    /** User plus system CPU time, in milliseconds */
+    cpuTime: number
+    /**
+     * CPU usage since the previous `getStats` call for this pid, where 100 is one fully busy core.
+     * Missing on the first call.
+     */
+    cpuPercent?: number
+    /** Resident set size (working set on Windows), in bytes */
+    rss: number
+    threads: number
+    /** Milliseconds since the Unix epoch, usable with `new Date()` */
+    startTime: number
+    /** Scheduler state on Linux, e.g. "R" (running), "S" (sleeping), "D" (uninterruptible) or "Z" (zombie) */
+    state?: string
   }
-  export function getGameProcesses(appid: number, linkedgame?: string | undefined | null): Array<ProcessInfo>
+  /**
+   * Samples a process' resource usage. Call it periodically for idle/hang detection, as `cpuPercent` is
+   * measured against the previous call.
+   */
+  export function getStats(pid: number): ProcessStats | null
   export function isProcessRunning(pid: number): boolean
   export function getWindowTitle(pid: number): string
 }
+export namespace wininfo {
+  export interface WindowInfo {
+    /** X11 window id, HWND on Windows, or the compositor's id for sway/Hyprland windows */
+    id: number
+    pid: number
+    title: string
+    /**
+     * What answered: "win32", "x11", "wmctrl", "sway", "hyprland", or "proc" when no window could be listed
+     * and the title was taken from the process' command line instead
+     */
+    backend: string
+  }
+  export interface Rect {
+    x: number
+    y: number
+    width: number
+    height: number
+  }
+  export interface Monitor {
+    index: number
+    /** e.g. "DP-1" on X11 or "\\.\DISPLAY1" on Windows */
+    name: string
+    bounds: Rect
+    primary: boolean
+  }
+  export interface WindowState {
+    id: number
+    pid: number
+    title: string
+    /** Screen coordinates of the window, excluding the window manager's frame on X11 */
+    bounds: Rect
+    /** Index into `getMonitors()` of the monitor showing most of the window */
+    monitor?: number
+    /** Covers its whole monitor, whether exclusive or borderless */
+    fullscreen: boolean
+    /** Direct3D exclusive fullscreen, where overlays can't be drawn on top. Always false on Linux. */
+    exclusiveFullscreen: boolean
+    /** Has no title bar or frame */
+    borderless: boolean
+    minimized: boolean
+    focused: boolean
+  }
+  export interface ForegroundChange {
+    /** The newly focused window, missing when nothing has focus */
+    window?: WindowInfo
+    previous?: WindowInfo
+  }
+  export interface WindowDetails {
+    id: number
+    pid: number
+    title: string
+    /** Class half of `WM_CLASS` on X11, the app id on Wayland or the window class name on Windows */
+    class?: string
+    /** Mapped on X11, shown on a visible workspace on Wayland, `IsWindowVisible` on Windows */
+    visible: boolean
+    minimized: boolean
+    /** Missing when the backend doesn't report geometry ("wmctrl" and "proc") */
+    bounds?: Rect
+    /** Set on the one window most likely to be the game's: titled, visible and not minimized, then the largest */
+    main: boolean
+    /** Same values as `WindowInfo.backend` */
+    backend: string
+  }
+  /** "wayland", "x11", "windows" or "unknown" */
+  export function getSessionType(): string
+  /** Every top-level window owned by `pid` */
+  export function getWindows(pid: number): Array<WindowInfo>
+  /** Every window owned by `pid` or its descendants, with the likely game window marked `main` */
+  export function getTreeWindows(pid: number): Array<WindowDetails>
+  /** Like `getTreeWindows`, for every process launched for `appid` */
+  export function getAppWindows(appid: number): Array<WindowDetails>
+  export function getMonitors(): Array<Monitor>
+  /** Geometry, monitor and fullscreen/focus state of `pid`'s main window, the one `WindowDetails.main` marks, for placing notifications */
+  export function getWindowState(pid: number): WindowState | null
+  /** The window that currently has focus. `pid` is 0 if the window doesn't say which process owns it. */
+  export function getForegroundWindow(): WindowInfo | null
+  /** Calls `handler` whenever focus moves to a different window, including once on start */
+  export function watchForeground(handler: (change: ForegroundChange) => void, intervalMs?: number | undefined | null): WatchHandle
+  /** Whether the focused window belongs to one of the processes launched for `appid` */
+  export function isAppFocused(appid: number): boolean
+}
+export namespace vdf {
+  export function parse(text: string): any
+  export function stringify(obj: any): string
+  /** Parses a binary VDF file such as shortcuts.vdf */
+  export function parseBinary(data: Buffer): any
+}
+export namespace steamdir {
+  export interface SteamRoot {
+    path: string
+    /** Where the candidate was found, e.g. "native", "flatpak", "snap" or "registry" */
+    source: string
+    /** Whether the directory looks like a Steam install (has a "steamapps" folder) */
+    valid: boolean
+    /** Whether a Steam client is currently running from this directory */
+    running: boolean
+    /** The root used by the rest of the API: the running client's root, otherwise the default install */
+    active: boolean
+  }
+  export function getSteamRoots(): Array<SteamRoot>
+  export function getSteamRoot(): string | null
+}
+export namespace library {
+  export interface InstalledGame {
+    appId: number
+    name: string
+    installDir: string
+    libraryPath: string
+    sizeOnDisk: number
+    buildId: number
+    /** Unix timestamp of the last update */
+    lastUpdated: number
+    stateFlags: number
+    /** Names of the bits set in `stateFlags`, e.g. "FullyInstalled" */
+    state: Array<string>
+  }
+  export function listLibraryFolders(steamRoot?: string | undefined | null): Array<string>
+  export function listInstalledGames(steamRoot?: string | undefined | null): Array<InstalledGame>
+  export function getInstalledGame(appId: number, steamRoot?: string | undefined | null): InstalledGame | null
+}
+export namespace accounts {
+  export interface LocalAccount {
+    steamId: PlayerSteamId
+    accountName: string
+    personaName: string
+    /** The account Steam will log in with on next start */
+    mostRecent: boolean
+    /** Whether the account is logged in to the running Steam client right now */
+    active: boolean
+    rememberPassword: boolean
+    wantsOfflineMode: boolean
+    allowAutoLogin: boolean
+    /** Unix timestamp of the last login */
+    timestamp: number
+    /** "<steam root>/userdata/<accountid>", if it exists */
+    userdataDir?: string
+  }
+  export function listAccounts(steamRoot?: string | undefined | null): Array<LocalAccount>
+  /** The logged in account if Steam is running, otherwise the one marked "MostRecent" */
+  export function getCurrentAccount(steamRoot?: string | undefined | null): LocalAccount | null
+  export function getUserdataDir(accountId: number, steamRoot?: string | undefined | null): string | null
+}
+export namespace runningapp {
+  export interface RunningAppChange {
+    /** 0 when no game is running */
+    appId: number
+    previousAppId: number
+  }
+  /** App id of the game Steam currently reports as running, read from Steam's registry ("registry.vdf" on Linux), or 0 */
+  export function getRunningAppId(): number
+  /**
+   * Calls `handler` whenever Steam's running app id changes, including once on start if a game is already running.
+   * On Linux and macOS each poll only stats registry.vdf, which is parsed again only after Steam rewrites it.
+   */
+  export function watchRunningApp(handler: (change: RunningAppChange) => void, intervalMs?: number | undefined | null): WatchHandle
+}
+export namespace appinfo {
+  export interface LaunchConfig {
+    /** Key of the entry under "config/launch", e.g. "0" */
+    id: string
+    /** Path relative to the install dir, as written by the developer (may use backslashes) */
+    executable: string
+    arguments?: string
+    workingDir?: string
+    description?: string
+    /** e.g. "default", "option1", "server", "none" */
+    launchType?: string
+    /** OSes the entry applies to, e.g. ["windows"]. Empty means every OS. */
+    osList: Array<string>
+    osArch?: string
+    /** Only offered when this beta branch is selected */
+    betaKey?: string
+  }
+  export interface AppInfo {
+    appId: number
+    name?: string
+    appType?: string
+    installDir?: string
+    icon?: string
+    clientIcon?: string
+    changeNumber: number
+    /** Unix timestamp of when Steam last refreshed this entry */
+    lastUpdated: number
+    launch: Array<LaunchConfig>
+  }
+  export function getAppInfo(appId: number, steamRoot?: string | undefined | null): AppInfo | null
+  /** Launch options configured for the app. Pass `allOs` to include entries for other operating systems. */
+  export function getLaunchConfigs(appId: number, allOs?: boolean | undefined | null, steamRoot?: string | undefined | null): Array<LaunchConfig>
+  /** The app's full appinfo.vdf entry as JSON */
+  export function getRawAppInfo(appId: number, steamRoot?: string | undefined | null): any | null
+}
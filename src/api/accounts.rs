@@ -0,0 +1,96 @@
+use super::localplayer::PlayerSteamId;
+use napi_derive::napi;
+
+#[napi]
+pub mod accounts {
+    use super::PlayerSteamId;
+    use std::path::{Path,PathBuf};
+    use steamworks::SteamId;
+    use log::error;
+    use crate::api::vdf;
+    use crate::api::steamdir::steamdir::{resolve_root,registry_dword};
+
+    #[napi(object)]
+    pub struct LocalAccount {
+        pub steam_id: PlayerSteamId,
+        pub account_name: String,
+        pub persona_name: String,
+        /// The account Steam will log in with on next start
+        pub most_recent: bool,
+        /// Whether the account is logged in to the running Steam client right now
+        pub active: bool,
+        pub remember_password: bool,
+        pub wants_offline_mode: bool,
+        pub allow_auto_login: bool,
+        /// Unix timestamp of the last login
+        pub timestamp: u32,
+        /// "<steam root>/userdata/<accountid>", if it exists
+        pub userdata_dir: Option<String>
+    }
+
+    fn userdata_dir(root: &Path,account_id: u32) -> Option<PathBuf> {
+        let dir = root.join("userdata").join(account_id.to_string());
+        dir.is_dir().then_some(dir)
+    }
+
+    pub(crate) fn local_accounts(root: &Path) -> Vec<LocalAccount> {
+        let Some(kv) = vdf::read_file(&root.join("config/loginusers.vdf")) else {
+            return Vec::new()
+        };
+
+        let Some(users) = kv.get_object("users") else {
+            error!("\"loginusers.vdf\" has no \"users\" block");
+            return Vec::new()
+        };
+
+        let active_user = registry_dword("Software\\Valve\\Steam\\ActiveProcess","ActiveUser").unwrap_or(0);
+        let flag = |user: &vdf::KeyValues,key: &str| user.get_str(key) == Some("1");
+
+        users
+            .iter()
+            .filter_map(|(id,user)| {
+                let steam_id = SteamId::from_raw(id.parse::<u64>().ok()?);
+                let user = user.as_object()?;
+                let account_id = steam_id.account_id().raw();
+
+                Some(LocalAccount {
+                    steam_id: PlayerSteamId::from_steamid(steam_id),
+                    account_name: user.get_str("AccountName").unwrap_or_default().to_string(),
+                    persona_name: user.get_str("PersonaName").unwrap_or_default().to_string(),
+                    most_recent: flag(user,"MostRecent"),
+                    active: active_user != 0 && active_user == account_id,
+                    remember_password: flag(user,"RememberPassword"),
+                    wants_offline_mode: flag(user,"WantsOfflineMode"),
+                    allow_auto_login: flag(user,"AllowAutoLogin"),
+                    timestamp: user.get_str("Timestamp").and_then(|t| t.parse().ok()).unwrap_or(0),
+                    userdata_dir: userdata_dir(root,account_id).map(|dir| dir.to_string_lossy().to_string())
+                })
+            })
+            .collect()
+    }
+
+    #[napi]
+    pub fn list_accounts(steam_root: Option<String>) -> Vec<LocalAccount> {
+        resolve_root(steam_root)
+            .map(|root| local_accounts(&root))
+            .unwrap_or_default()
+    }
+
+    /// The logged in account if Steam is running, otherwise the one marked "MostRecent"
+    #[napi]
+    pub fn get_current_account(steam_root: Option<String>) -> Option<LocalAccount> {
+        let accounts = list_accounts(steam_root);
+
+        let index = accounts.iter().position(|account| account.active)
+            .or_else(|| accounts.iter().position(|account| account.most_recent))?;
+
+        accounts.into_iter().nth(index)
+    }
+
+    #[napi]
+    pub fn get_userdata_dir(account_id: u32,steam_root: Option<String>) -> Option<String> {
+        resolve_root(steam_root)
+            .and_then(|root| userdata_dir(&root,account_id))
+            .map(|dir| dir.to_string_lossy().to_string())
+    }
+}
@@ -0,0 +1,372 @@
+use napi_derive::napi;
+
+#[napi]
+pub mod appinfo {
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{BufRead,BufReader,Read,Seek,SeekFrom};
+    use std::path::{Path,PathBuf};
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+    use log::{debug,error};
+    use crate::api::vdf::{self,KeyValues};
+    use crate::api::steamdir::steamdir::resolve_root;
+
+    const MAGIC_V27: u32 = 0x07564427;
+    const MAGIC_V28: u32 = 0x07564428;
+    /// Keys are indexes into a string table stored at the end of the file
+    const MAGIC_V29: u32 = 0x07564429;
+
+    #[napi(object)]
+    #[derive(Debug,Clone,PartialEq)]
+    pub struct LaunchConfig {
+        /// Key of the entry under "config/launch", e.g. "0"
+        pub id: String,
+        /// Path relative to the install dir, as written by the developer (may use backslashes)
+        pub executable: String,
+        pub arguments: Option<String>,
+        pub working_dir: Option<String>,
+        pub description: Option<String>,
+        /// e.g. "default", "option1", "server", "none"
+        pub launch_type: Option<String>,
+        /// OSes the entry applies to, e.g. ["windows"]. Empty means every OS.
+        pub os_list: Vec<String>,
+        pub os_arch: Option<String>,
+        /// Only offered when this beta branch is selected
+        pub beta_key: Option<String>
+    }
+
+    #[napi(object)]
+    #[derive(Debug,Clone,PartialEq)]
+    pub struct AppInfo {
+        pub app_id: u32,
+        pub name: Option<String>,
+        pub app_type: Option<String>,
+        pub install_dir: Option<String>,
+        pub icon: Option<String>,
+        pub client_icon: Option<String>,
+        pub change_number: u32,
+        /// Unix timestamp of when Steam last refreshed this entry
+        pub last_updated: u32,
+        pub launch: Vec<LaunchConfig>
+    }
+
+    #[derive(Debug,Clone)]
+    struct Entry {
+        last_updated: u32,
+        change_number: u32,
+        kv: KeyValues
+    }
+
+    /// Parsed entry (or its absence) and the appinfo.vdf mtime it was read at
+    type CachedEntry = (SystemTime,Option<Entry>);
+
+    lazy_static! {
+        /// Parsed entries by file and app id, dropped when appinfo.vdf is rewritten
+        static ref ENTRIES: Mutex<HashMap<(PathBuf,u32),CachedEntry>> = Mutex::new(HashMap::new());
+    }
+
+    fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+        let mut bytes = [0;4];
+        reader.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_string_table(reader: &mut BufReader<File>,offset: u64) -> std::io::Result<Vec<String>> {
+        let entries = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(offset))?;
+
+        let count = read_u32(reader)?;
+        let mut strings = Vec::with_capacity(count.min(1 << 20) as usize);
+
+        for _ in 0..count {
+            let mut bytes = Vec::new();
+            reader.read_until(0,&mut bytes)?;
+            bytes.pop();
+            strings.push(String::from_utf8_lossy(&bytes).to_string());
+        }
+
+        reader.seek(SeekFrom::Start(entries))?;
+        Ok(strings)
+    }
+
+    /// Scans appinfo.vdf for one app, skipping over every other entry without parsing it
+    fn read_entry(path: &Path,app_id: u32) -> Result<Option<Entry>,String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        let mut reader = BufReader::new(file);
+        let io = |err: std::io::Error| err.to_string();
+
+        let magic = read_u32(&mut reader).map_err(io)?;
+        let _universe = read_u32(&mut reader).map_err(io)?;
+
+        let strings = match magic {
+            MAGIC_V29 => {
+                let mut offset = [0;8];
+                reader.read_exact(&mut offset).map_err(io)?;
+                Some(read_string_table(&mut reader,i64::from_le_bytes(offset) as u64).map_err(io)?)
+            },
+            MAGIC_V27 | MAGIC_V28 => None,
+            _ => return Err(format!("Unsupported appinfo.vdf version 0x{:08x}",magic))
+        };
+
+        // info state, last updated, PICS token, text SHA-1, change number and, from v28, the binary SHA-1
+        let header_len = if magic == MAGIC_V27 { 40 } else { 60 };
+
+        loop {
+            let id = match read_u32(&mut reader) {
+                Ok(id) => id,
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err.to_string())
+            };
+
+            if id == 0 {
+                return Ok(None)
+            }
+
+            let size = read_u32(&mut reader).map_err(io)?;
+
+            if id != app_id {
+                reader.seek_relative(size as i64).map_err(io)?;
+                continue
+            }
+
+            let mut data = vec![0;size as usize];
+            reader.read_exact(&mut data).map_err(io)?;
+
+            if data.len() < header_len {
+                return Err(format!("Entry for AppId {} is truncated",app_id))
+            }
+
+            let mut header = vdf::BinaryReader::new(&data[..header_len]);
+            let read = |err: vdf::BinaryVdfError| err.to_string();
+            let _info_state = header.u32().map_err(read)?;
+            let last_updated = header.u32().map_err(read)?;
+            header.take(28).map_err(read)?;
+            let change_number = header.u32().map_err(read)?;
+
+            let kv = vdf::parse_binary(&data[header_len..],strings.as_deref())
+                .map_err(|err| format!("AppId {}: {}",app_id,err))?;
+
+            return Ok(Some(Entry { last_updated, change_number, kv }))
+        }
+    }
+
+    fn appinfo_path(steam_root: Option<String>) -> Option<PathBuf> {
+        let path = resolve_root(steam_root)?.join("appcache/appinfo.vdf");
+        path.is_file().then_some(path)
+    }
+
+    fn entry(app_id: u32,steam_root: Option<String>) -> Option<Entry> {
+        let path = appinfo_path(steam_root)?;
+        let modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+        let key = (path.clone(),app_id);
+
+        if let Some((cached_at,entry)) = ENTRIES.lock().unwrap().get(&key) {
+            if *cached_at == modified {
+                return entry.clone()
+            }
+        }
+
+        let entry = match read_entry(&path,app_id) {
+            Ok(entry) => entry,
+            Err(err) => {
+                error!("Failed to read \"{}\": {}",path.display(),err);
+                None
+            }
+        };
+
+        if entry.is_none() {
+            debug!("AppId {} is not in \"{}\"",app_id,path.display());
+        }
+
+        ENTRIES.lock().unwrap().insert(key,(modified,entry.clone()));
+        entry
+    }
+
+    fn optional(kv: &KeyValues,key: &str) -> Option<String> {
+        kv.get_str(key).filter(|value| !value.is_empty()).map(str::to_string)
+    }
+
+    fn launch_configs(app: &KeyValues) -> Vec<LaunchConfig> {
+        let Some(launch) = app.path(&["config","launch"]).and_then(|launch| launch.as_object()) else {
+            return Vec::new()
+        };
+
+        launch
+            .iter()
+            .filter_map(|(id,entry)| {
+                let entry = entry.as_object()?;
+                let executable = optional(entry,"executable")?;
+                let config = entry.get_object("config");
+
+                Some(LaunchConfig {
+                    id: id.clone(),
+                    executable,
+                    arguments: optional(entry,"arguments"),
+                    working_dir: optional(entry,"workingdir"),
+                    description: optional(entry,"description"),
+                    launch_type: optional(entry,"type"),
+                    os_list: config
+                        .and_then(|config| config.get_str("oslist"))
+                        .map(|list| list.split(',').map(str::trim).filter(|os| !os.is_empty()).map(str::to_string).collect())
+                        .unwrap_or_default(),
+                    os_arch: config.and_then(|config| optional(config,"osarch")),
+                    beta_key: config.and_then(|config| optional(config,"betakey"))
+                })
+            })
+            .collect()
+    }
+
+    fn app_info(app_id: u32,entry: &Entry) -> AppInfo {
+        // Older files nest everything under an "appinfo" block
+        let app = entry.kv.get_object("appinfo").unwrap_or(&entry.kv);
+        let common = app.get_object("common");
+
+        AppInfo {
+            app_id,
+            name: common.and_then(|common| optional(common,"name")),
+            app_type: common.and_then(|common| optional(common,"type")),
+            install_dir: app.get_object("config").and_then(|config| optional(config,"installdir")),
+            icon: common.and_then(|common| optional(common,"icon")),
+            client_icon: common.and_then(|common| optional(common,"clienticon")),
+            change_number: entry.change_number,
+            last_updated: entry.last_updated,
+            launch: launch_configs(app)
+        }
+    }
+
+    /// Name of the current OS as used in "oslist"
+    pub(crate) fn current_os() -> &'static str {
+        if cfg!(target_os="windows") {
+            "windows"
+        } else if cfg!(target_os="macos") {
+            "macos"
+        } else {
+            "linux"
+        }
+    }
+
+    impl LaunchConfig {
+        pub(crate) fn applies_to(&self,os: &str) -> bool {
+            self.os_list.is_empty() || self.os_list.iter().any(|entry| entry.eq_ignore_ascii_case(os))
+        }
+
+        /// File name of the executable, e.g. "Game.exe" for "bin\\win64\\Game.exe"
+        pub(crate) fn exe_name(&self) -> &str {
+            self.executable.rsplit(['/','\\']).next().unwrap_or(&self.executable)
+        }
+    }
+
+    #[napi]
+    pub fn get_app_info(app_id: u32,steam_root: Option<String>) -> Option<AppInfo> {
+        entry(app_id,steam_root).map(|entry| app_info(app_id,&entry))
+    }
+
+    /// Launch options configured for the app. Pass `allOs` to include entries for other operating systems.
+    #[napi]
+    pub fn get_launch_configs(app_id: u32,all_os: Option<bool>,steam_root: Option<String>) -> Vec<LaunchConfig> {
+        let launch = get_app_info(app_id,steam_root).map(|info| info.launch).unwrap_or_default();
+
+        match all_os.unwrap_or(false) {
+            true => launch,
+            false => launch.into_iter().filter(|config| config.applies_to(current_os())).collect()
+        }
+    }
+
+    /// The app's full appinfo.vdf entry as JSON
+    #[napi]
+    pub fn get_raw_app_info(app_id: u32,steam_root: Option<String>) -> Option<serde_json::Value> {
+        entry(app_id,steam_root).map(|entry| vdf::to_json(&entry.kv))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::api::testdir::TempDir;
+
+        fn entry_bytes(app_id: u32,kv: &[u8],v27: bool) -> Vec<u8> {
+            let header_len = if v27 { 40 } else { 60 };
+            let mut header = vec![0;header_len];
+            header[4..8].copy_from_slice(&1700000000u32.to_le_bytes());
+            header[36..40].copy_from_slice(&42u32.to_le_bytes());
+
+            let mut data = app_id.to_le_bytes().to_vec();
+            data.extend(((header_len + kv.len()) as u32).to_le_bytes());
+            data.extend(header);
+            data.extend(kv);
+            data
+        }
+
+        /// The file lives as long as the returned dir
+        fn write(name: &str,data: &[u8]) -> (TempDir,PathBuf) {
+            let dir = TempDir::new(&format!("appinfo-{}",name));
+            let path = dir.join("appinfo.vdf");
+            std::fs::write(&path,data).unwrap();
+            (dir,path)
+        }
+
+        #[test]
+        fn reads_v28_entries() {
+            let mut kv = vec![0x00];
+            kv.extend(b"appinfo\0");
+            kv.push(0x00);
+            kv.extend(b"common\0\x01name\0Other\0\x08\x08\x08");
+
+            let mut wanted = vec![0x00];
+            wanted.extend(b"appinfo\0\x00config\0\x00launch\0\x00");
+            wanted.extend(b"0\0\x01executable\0bin\\win64\\Game.exe\0\x00config\0\x01oslist\0windows\0\x08\x08\x08\x08\x08\x08");
+
+            let mut data = MAGIC_V28.to_le_bytes().to_vec();
+            data.extend(1u32.to_le_bytes());
+            data.extend(entry_bytes(10,&kv,false));
+            data.extend(entry_bytes(20,&wanted,false));
+            data.extend(0u32.to_le_bytes());
+
+            let (_dir,path) = write("v28",&data);
+            let entry = read_entry(&path,20).unwrap().unwrap();
+            let missing = read_entry(&path,30).unwrap();
+
+            let info = app_info(20,&entry);
+            assert_eq!(info.change_number,42);
+            assert_eq!(info.last_updated,1700000000);
+            assert_eq!(info.launch.len(),1);
+            assert_eq!(info.launch[0].exe_name(),"Game.exe");
+            assert_eq!(info.launch[0].os_list,vec!["windows".to_string()]);
+            assert!(info.launch[0].applies_to("windows"));
+            assert!(!info.launch[0].applies_to("linux"));
+            assert!(missing.is_none());
+        }
+
+        #[test]
+        fn reads_v29_string_table() {
+            let mut kv = vec![0x00];
+            kv.extend(0u32.to_le_bytes());
+            kv.push(0x01);
+            kv.extend(1u32.to_le_bytes());
+            kv.extend(b"Portal\0\x08");
+
+            let mut entries = entry_bytes(400,&kv,false);
+            entries.extend(0u32.to_le_bytes());
+
+            let mut data = MAGIC_V29.to_le_bytes().to_vec();
+            data.extend(1u32.to_le_bytes());
+            data.extend(((16 + entries.len()) as i64).to_le_bytes());
+            data.extend(entries);
+            data.extend(2u32.to_le_bytes());
+            data.extend(b"common\0name\0");
+
+            let (_dir,path) = write("v29",&data);
+            let entry = read_entry(&path,400).unwrap().unwrap();
+
+            assert_eq!(app_info(400,&entry).name.as_deref(),Some("Portal"));
+        }
+
+        #[test]
+        fn rejects_unknown_versions() {
+            let (_dir,path) = write("unknown",&[0x30,0x44,0x56,0x07,1,0,0,0]);
+            let result = read_entry(&path,1);
+
+            assert!(result.unwrap_err().starts_with("Unsupported appinfo.vdf version"));
+        }
+    }
+}
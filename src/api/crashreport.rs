@@ -0,0 +1,136 @@
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path,PathBuf};
+use std::sync::Mutex;
+use log::Record;
+use serde_json::{Value,json};
+
+/// Log lines kept for the next crash report
+const RECENT_LINES: usize = 200;
+
+lazy_static! {
+    static ref RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(RECENT_LINES));
+    static ref DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Where crash reports are written, set by `initLogger`
+pub fn set_dir(dir: PathBuf) {
+    *DIR.lock().unwrap() = Some(dir);
+}
+
+pub fn dir() -> Option<PathBuf> {
+    DIR.try_lock().ok()?.clone()
+}
+
+/// Log sink keeping the last `RECENT_LINES` records
+pub fn remember(record: &Record) {
+    let line = format!(
+        "{} [{}] {}: {}",
+        chrono::Local::now().format("%H:%M:%S%.3f"),
+        record.level(),
+        record.target(),
+        record.args()
+    );
+
+    let mut recent = RECENT.lock().unwrap_or_else(|err| err.into_inner());
+
+    if recent.len() == RECENT_LINES {
+        recent.pop_front();
+    }
+
+    recent.push_back(line);
+}
+
+/// Doesn't wait for the lock, as the panic may have happened while it was held
+fn recent_lines() -> Vec<String> {
+    RECENT.try_lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
+
+/// Everything needed to make sense of a panic without the rest of the logs
+pub fn report(location: &str,message: &str,backtrace: &Backtrace) -> Value {
+    json!({
+        "time": chrono::Local::now().to_rfc3339(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": format!("{} {}",std::env::consts::OS,std::env::consts::ARCH),
+        "thread": std::thread::current().name().unwrap_or("unnamed"),
+        "location": location,
+        "message": message,
+        "appId": crate::client::app_id(),
+        "steamInitialised": crate::client::is_initialised(),
+        "backtrace": backtrace.to_string(),
+        "recentLog": recent_lines()
+    })
+}
+
+/// Writes `report` as "crash-<time>-<pid>.json", named so reports sort oldest to newest
+pub fn write(dir: &Path,report: &Value) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+
+    let path = dir.join(format!(
+        "crash-{}-{}.json",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%.3f"),
+        std::process::id()
+    ));
+
+    std::fs::write(&path,serde_json::to_string_pretty(report)?)?;
+    Ok(path)
+}
+
+/// The newest report in `dir`, from this session or an earlier one
+pub fn latest(dir: &Path) -> Option<(PathBuf,Value)> {
+    let path = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".json")))
+        .max()?;
+
+    let report = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+    Some((path,report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::testdir::TempDir;
+
+    #[test]
+    fn keeps_recent_lines_in_report() {
+        for index in 0..RECENT_LINES + 5 {
+            remember(&Record::builder().level(log::Level::Info).target("test").args(format_args!("line {}",index)).build());
+        }
+
+        let report = report("src/api/log.rs:1:1","boom",&Backtrace::force_capture());
+        let recent = report["recentLog"].as_array().unwrap();
+
+        assert_eq!(recent.len(),RECENT_LINES);
+        assert!(recent[0].as_str().unwrap().ends_with("INFO] test: line 5"));
+        assert!(recent.last().unwrap().as_str().unwrap().ends_with(&format!("line {}",RECENT_LINES + 4)));
+        assert_eq!(report["message"],"boom");
+        assert_eq!(report["version"],env!("CARGO_PKG_VERSION"));
+        assert!(report["appId"].is_null());
+        assert_eq!(report["steamInitialised"],false);
+        assert!(!report["backtrace"].as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn finds_latest_report() {
+        let temp = TempDir::new("crashes");
+        let dir = temp.join("crashes");
+
+        assert!(latest(&dir).is_none());
+
+        write(&dir,&json!({ "message": "first" })).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let path = write(&dir,&json!({ "message": "second" })).unwrap();
+        std::fs::write(dir.join("notes.txt"),"not a report").unwrap();
+
+        let (found,report) = latest(&dir).unwrap();
+
+        assert_eq!(found,path);
+        assert_eq!(report["message"],"second");
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::path::{Path,PathBuf};
+use std::sync::{Arc,Mutex};
+use std::time::UNIX_EPOCH;
+use log::{debug,info,warn};
+use serde_json::{json,Value};
+
+/// Cached result of globbing an app's install dir for executables, persisted as "<dir>/<appid>.json"
+#[derive(Debug,Clone,PartialEq)]
+struct IndexEntry {
+    install_dir: String,
+    build_id: u32,
+    /// Modification times of the install dir and every dir below it, taken before the scan
+    dirs: Vec<(String,u64)>,
+    exes: Vec<PathBuf>
+}
+
+lazy_static! {
+    static ref INDEX_DIR: Mutex<PathBuf> = Mutex::new(default_dir());
+    static ref ENTRIES: Mutex<HashMap<u32,IndexEntry>> = Mutex::new(HashMap::new());
+    /// Held while an app's index is checked and built, so concurrent lookups wait for one scan instead of each walking the dir
+    static ref BUILDING: Mutex<HashMap<u32,Arc<Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// "steamworksjs/exeindex" under the platform's cache dir, which unlike the temp dir survives a reboot
+fn default_dir() -> PathBuf {
+    let non_empty = |var: &str| std::env::var_os(var).filter(|value| !value.is_empty()).map(PathBuf::from);
+
+    let cache = if cfg!(target_os="windows") {
+        non_empty("LOCALAPPDATA")
+    } else if cfg!(target_os="macos") {
+        non_empty("HOME").map(|home| home.join("Library/Caches"))
+    } else {
+        non_empty("XDG_CACHE_HOME").or_else(|| non_empty("HOME").map(|home| home.join(".cache")))
+    };
+
+    cache.unwrap_or_else(std::env::temp_dir).join("steamworksjs").join("exeindex")
+}
+
+pub fn set_dir(dir: PathBuf) {
+    *INDEX_DIR.lock().unwrap() = dir;
+    ENTRIES.lock().unwrap().clear();
+}
+
+fn entry_path(appid: u32) -> PathBuf {
+    INDEX_DIR.lock().unwrap().join(format!("{}.json",appid))
+}
+
+fn mtime(dir: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(dir).and_then(|meta| meta.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+/// `dir` and every dir below it, without following symlinks
+fn dirs_below(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![dir.to_path_buf()];
+    let mut index = 0;
+
+    while index < dirs.len() {
+        if let Ok(entries) = std::fs::read_dir(&dirs[index]) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    dirs.push(entry.path());
+                }
+            }
+        }
+
+        index += 1;
+    }
+
+    dirs
+}
+
+impl IndexEntry {
+    /// Every dir's mtime is read before `scan` runs, so anything added during the scan invalidates the entry
+    fn build(install_dir: &str,build_id: u32,scan: impl FnOnce() -> Vec<PathBuf>) -> Self {
+        let dirs = dirs_below(Path::new(install_dir))
+            .into_iter()
+            .map(|dir| {
+                let modified = mtime(&dir).unwrap_or(0);
+                (dir.to_string_lossy().to_string(),modified)
+            })
+            .collect();
+
+        Self {
+            install_dir: install_dir.to_string(),
+            build_id,
+            dirs,
+            exes: scan()
+        }
+    }
+
+    /// Stale if the app moved, was updated, or any dir had files or dirs added, removed or renamed
+    fn is_valid(&self,install_dir: &str,build_id: u32) -> bool {
+        self.install_dir == install_dir
+            && self.build_id == build_id
+            && self.dirs.iter().all(|(dir,modified)| mtime(Path::new(dir)) == Some(*modified))
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "installDir": self.install_dir,
+            "buildId": self.build_id,
+            "dirs": self.dirs.iter().map(|(dir,modified)| json!({ "path": dir, "mtime": modified })).collect::<Vec<_>>(),
+            "exes": self.exes.iter().map(|exe| exe.to_string_lossy().to_string()).collect::<Vec<_>>()
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            install_dir: value["installDir"].as_str()?.to_string(),
+            build_id: value["buildId"].as_u64()? as u32,
+            dirs: value["dirs"]
+                .as_array()?
+                .iter()
+                .map(|dir| Some((dir["path"].as_str()?.to_string(),dir["mtime"].as_u64()?)))
+                .collect::<Option<Vec<_>>>()?,
+            exes: value["exes"]
+                .as_array()?
+                .iter()
+                .map(|exe| exe.as_str().map(PathBuf::from))
+                .collect::<Option<Vec<_>>>()?
+        })
+    }
+}
+
+fn load(appid: u32) -> Option<IndexEntry> {
+    let path = entry_path(appid);
+    let text = std::fs::read_to_string(&path).ok()?;
+
+    match serde_json::from_str::<Value>(&text).ok().as_ref().and_then(IndexEntry::from_json) {
+        Some(entry) => Some(entry),
+        None => {
+            warn!("Ignoring corrupt exe index \"{}\"",path.display());
+            None
+        }
+    }
+}
+
+fn save(appid: u32,entry: &IndexEntry) {
+    let path = entry_path(appid);
+
+    let written = path
+        .parent()
+        .map_or(Ok(()),std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path,entry.to_json().to_string()));
+
+    if let Err(err) = written {
+        warn!("Unable to write exe index \"{}\": {}",path.display(),err);
+    }
+}
+
+/// Returns the indexed exes for `appid`, calling `build` to rescan the install dir only when the index is missing or stale
+pub fn exes<F>(appid: u32,install_dir: &str,build_id: u32,build: F) -> Vec<PathBuf>
+where
+    F: FnOnce() -> Vec<PathBuf>
+{
+    let lock = BUILDING.lock().unwrap().entry(appid).or_default().clone();
+    let _building = lock.lock().unwrap();
+
+    let cached = ENTRIES.lock().unwrap().get(&appid).cloned().or_else(|| load(appid));
+
+    if let Some(entry) = cached.filter(|entry| entry.is_valid(install_dir,build_id)) {
+        debug!("Using cached exe index for AppId {} ({} exes)",appid,entry.exes.len());
+        let exes = entry.exes.clone();
+        ENTRIES.lock().unwrap().insert(appid,entry);
+        return exes
+    }
+
+    let started = std::time::Instant::now();
+    let entry = IndexEntry::build(install_dir,build_id,build);
+    info!("Indexed {} exes for AppId {} in {:?}",entry.exes.len(),appid,started.elapsed());
+
+    save(appid,&entry);
+    let exes = entry.exes.clone();
+    ENTRIES.lock().unwrap().insert(appid,entry);
+    exes
+}
+
+pub fn clear(appid: Option<u32>) {
+    let mut entries = ENTRIES.lock().unwrap();
+
+    let appids = match appid {
+        Some(appid) => vec![appid],
+        None => entries.keys().copied().collect()
+    };
+
+    for appid in appids {
+        entries.remove(&appid);
+        let _ = std::fs::remove_file(entry_path(appid));
+    }
+
+    if appid.is_none() {
+        remove_entry_files(&INDEX_DIR.lock().unwrap());
+    }
+}
+
+/// Deletes the "<appid>.json" files in `dir`, leaving anything else alone as the dir may be shared
+fn remove_entry_files(dir: &Path) {
+    let Ok(files) = std::fs::read_dir(dir) else {
+        return
+    };
+
+    for path in files.filter_map(|file| file.ok()).map(|file| file.path()) {
+        let is_entry = path.extension().is_some_and(|ext| ext == "json")
+            && path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| stem.parse::<u32>().is_ok());
+
+        if is_entry {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::testdir::TempDir;
+    use std::sync::MutexGuard;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::time::Duration;
+
+    /// Points the index at `dir`. `set_dir` is global and drops every cached entry, so tests using it take turns.
+    fn use_index_dir(dir: &TempDir) -> MutexGuard<'static,()> {
+        static TURN: Mutex<()> = Mutex::new(());
+
+        let turn = TURN.lock().unwrap_or_else(|err| err.into_inner());
+        set_dir(dir.to_path_buf());
+        turn
+    }
+
+    /// Lists every file, counting how often the install dir was scanned
+    fn scan(dir: &Path,scans: &AtomicUsize) -> Vec<PathBuf> {
+        scans.fetch_add(1,Ordering::SeqCst);
+
+        let mut files = dirs_below(dir)
+            .iter()
+            .flat_map(|dir| std::fs::read_dir(dir).into_iter().flatten().filter_map(|entry| entry.ok()))
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+
+        files.sort();
+        files
+    }
+
+    /// File timestamps come from a coarse clock, so changes made straight after a build could keep the same mtime
+    fn touch(path: PathBuf) {
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path,"").unwrap();
+    }
+
+    #[test]
+    fn rebuilds_only_when_stale() {
+        let index = TempDir::new("exeindex-stale");
+        let _turn = use_index_dir(&index);
+        let install = TempDir::new("exeindex-install");
+        let dir = install.to_string_lossy().to_string();
+        let scans = AtomicUsize::new(0);
+        let lookup = |build_id| exes(9001,&dir,build_id,|| scan(&install,&scans));
+
+        std::fs::create_dir_all(install.join("data")).unwrap();
+        touch(install.join("game"));
+
+        assert_eq!(lookup(0),vec![install.join("game")]);
+        assert_eq!(lookup(0).len(),1);
+        assert_eq!(scans.load(Ordering::SeqCst),1);
+
+        // Persisted, so a new session reuses it
+        ENTRIES.lock().unwrap().remove(&9001);
+        lookup(0);
+        assert_eq!(scans.load(Ordering::SeqCst),1);
+
+        // An exe in a dir that had none
+        touch(install.join("data/tool"));
+        assert_eq!(lookup(0).len(),2);
+        assert_eq!(scans.load(Ordering::SeqCst),2);
+
+        // An exe in a dir that didn't exist
+        touch(install.join("data/new/nested/helper"));
+        assert_eq!(lookup(0).len(),3);
+        assert_eq!(scans.load(Ordering::SeqCst),3);
+
+        lookup(7);
+        lookup(7);
+        assert_eq!(scans.load(Ordering::SeqCst),4);
+    }
+
+    #[test]
+    fn concurrent_lookups_share_one_scan() {
+        let index = TempDir::new("exeindex-concurrent");
+        let _turn = use_index_dir(&index);
+        let install = TempDir::new("exeindex-concurrent-install");
+        let dir = install.to_string_lossy().to_string();
+        let scans = AtomicUsize::new(0);
+        touch(install.join("game"));
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| exes(9002,&dir,1,|| {
+                    std::thread::sleep(Duration::from_millis(50));
+                    scan(&install,&scans)
+                }));
+            }
+        });
+
+        assert_eq!(scans.load(Ordering::SeqCst),1);
+    }
+
+    #[test]
+    fn clearing_keeps_unrelated_files() {
+        let dir = TempDir::new("exeindex-clear");
+
+        for name in ["440.json","settings.json","-1.json","440.json.bak","notes.txt"] {
+            std::fs::write(dir.join(name),"{}").unwrap();
+        }
+
+        remove_entry_files(&dir);
+
+        let mut left = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|file| file.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        left.sort();
+
+        assert_eq!(left,["-1.json","440.json.bak","notes.txt","settings.json"]);
+    }
+}
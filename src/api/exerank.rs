@@ -0,0 +1,244 @@
+use napi_derive::napi;
+use std::path::{Path,PathBuf};
+use std::sync::Mutex;
+use glob::{Pattern,MatchOptions};
+
+/// Helpers, installers and anti-cheat services that ship alongside games but are never the game itself
+const DEFAULT_DENY: [&str;24] = [
+    "UnityCrashHandler*",
+    "*CrashHandler*",
+    "*CrashReport*",
+    "crashpad_handler*",
+    "CrashSender*",
+    "vc_redist*",
+    "vcredist*",
+    "DXSETUP*",
+    "dxwebsetup*",
+    "dotNetFx*",
+    "NDP*-KB*",
+    "oalinst*",
+    "PhysX*",
+    "UE4PrereqSetup*",
+    "UEPrereqSetup*",
+    "EasyAntiCheat*",
+    "EACLauncher*",
+    "BEService*",
+    "BattlEye*",
+    "unins0*",
+    "uninstall*",
+    "steamerrorreporter*",
+    "CefSharp.BrowserSubprocess*",
+    "QtWebEngineProcess*"
+];
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct ExeFilters {
+    /// Case-insensitive glob patterns matched against file names, excluded from matching
+    pub deny: Vec<String>,
+    /// Patterns that are always kept and ranked first, overriding `deny`
+    pub allow: Vec<String>
+}
+
+impl Default for ExeFilters {
+    fn default() -> Self {
+        Self {
+            deny: DEFAULT_DENY.iter().map(|pattern| pattern.to_string()).collect(),
+            allow: Vec::new()
+        }
+    }
+}
+
+#[napi(object)]
+pub struct RankedExe {
+    pub path: String,
+    pub name: String,
+    pub score: i32,
+    /// Why the score is what it is, e.g. "matches install dir name (+40)"
+    pub reasons: Vec<String>,
+    /// Excluded exes are still listed, but never used for process matching
+    pub excluded: bool
+}
+
+lazy_static! {
+    static ref FILTERS: Mutex<ExeFilters> = Mutex::new(ExeFilters::default());
+}
+
+pub fn filters() -> ExeFilters {
+    FILTERS.lock().unwrap().clone()
+}
+
+pub fn set_filters(filters: ExeFilters) {
+    *FILTERS.lock().unwrap() = filters;
+}
+
+fn matching_pattern<'a>(patterns: &'a [String],name: &str) -> Option<&'a str> {
+    let options = MatchOptions {
+        case_sensitive: false,
+        ..MatchOptions::default()
+    };
+
+    patterns
+        .iter()
+        .find(|pattern| match Pattern::new(pattern) {
+            Ok(glob) => glob.matches_with(name,options),
+            Err(_) => pattern.eq_ignore_ascii_case(name)
+        })
+        .map(String::as_str)
+}
+
+/// Lowercase alphanumerics only, so "Hollow Knight" and "hollow_knight.x86_64" compare equal
+fn normalise(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn name_similarity(stem: &str,dir: &str) -> i32 {
+    let (stem,dir) = (normalise(stem),normalise(dir));
+
+    if stem.is_empty() || dir.is_empty() {
+        return 0
+    }
+
+    if stem == dir {
+        return 40
+    }
+
+    if stem.contains(&dir) || dir.contains(&stem) {
+        return 25
+    }
+
+    let common = stem.chars().zip(dir.chars()).take_while(|(a,b)| a == b).count();
+    (common * 20 / stem.len().max(dir.len())) as i32
+}
+
+fn rank_exe(install_dir: &Path,path: &Path,filters: &ExeFilters) -> RankedExe {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let lower = name.to_lowercase();
+    let mut score = 0;
+    let mut reasons = Vec::new();
+    let mut excluded = false;
+
+    if let Some(pattern) = matching_pattern(&filters.allow,&name) {
+        score += 100;
+        reasons.push(format!("allow-listed by \"{}\" (+100)",pattern));
+    } else if let Some(pattern) = matching_pattern(&filters.deny,&name) {
+        excluded = true;
+        reasons.push(format!("deny-listed by \"{}\"",pattern));
+    } else if lower.ends_with(".so") || lower.contains(".so.") || lower.ends_with(".dll") {
+        excluded = true;
+        reasons.push("shared library".to_string());
+    }
+
+    let size = std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    let size_score = (size / (1024 * 1024)).min(30) as i32;
+    score += size_score;
+    reasons.push(format!("{:.1} MB (+{})",size as f64 / (1024.0 * 1024.0),size_score));
+
+    let depth = path
+        .strip_prefix(install_dir)
+        .map(|relative| relative.components().count().saturating_sub(1))
+        .unwrap_or(usize::MAX);
+
+    let depth_score = match depth {
+        0 => 20,
+        1 => 10,
+        2 => 5,
+        _ => 0
+    };
+    score += depth_score;
+    reasons.push(format!("depth {} (+{})",depth.min(99),depth_score));
+
+    let stem = lower.split('.').next().unwrap_or(&lower);
+    let dir_name = install_dir.file_name().map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default();
+    let similarity = name_similarity(stem,&dir_name);
+
+    if similarity > 0 {
+        score += similarity;
+        reasons.push(format!("similar to install dir \"{}\" (+{})",dir_name,similarity));
+    }
+
+    if lower.ends_with(".sh") {
+        score -= 5;
+        reasons.push("shell script (-5)".to_string());
+    }
+
+    RankedExe {
+        path: path.to_string_lossy().to_string(),
+        name,
+        score,
+        reasons,
+        excluded
+    }
+}
+
+/// Scores every candidate and sorts them best first, with excluded exes last
+pub fn rank(install_dir: &Path,exes: &[PathBuf]) -> Vec<RankedExe> {
+    rank_with(install_dir,exes,&filters())
+}
+
+fn rank_with(install_dir: &Path,exes: &[PathBuf],filters: &ExeFilters) -> Vec<RankedExe> {
+    let mut ranked = exes
+        .iter()
+        .map(|path| rank_exe(install_dir,path,filters))
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|a,b| a.excluded.cmp(&b.excluded).then(b.score.cmp(&a.score)).then(a.name.cmp(&b.name)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // None of these paths exist, so every exe scores 0 for size
+    const INSTALL_DIR: &str = "/games/Hollow Knight";
+
+    fn rank_names(filters: &ExeFilters,exes: &[&str]) -> Vec<RankedExe> {
+        let exes = exes.iter().map(|exe| Path::new(INSTALL_DIR).join(exe)).collect::<Vec<_>>();
+        rank_with(Path::new(INSTALL_DIR),&exes,filters)
+    }
+
+    fn scores(ranked: &[RankedExe]) -> Vec<(&str,i32,bool)> {
+        ranked.iter().map(|exe| (exe.name.as_str(),exe.score,exe.excluded)).collect()
+    }
+
+    #[test]
+    fn scores_depth_name_and_scripts() {
+        let ranked = rank_names(&ExeFilters::default(),&["hollow_knight.x86_64","bin/launch.sh","a/b/c/tool"]);
+
+        assert_eq!(scores(&ranked),vec![("hollow_knight.x86_64",60,false),("launch.sh",5,false),("tool",0,false)]);
+        assert!(ranked[0].reasons.contains(&"similar to install dir \"Hollow Knight\" (+40)".to_string()));
+        assert!(ranked[1].reasons.contains(&"shell script (-5)".to_string()));
+    }
+
+    #[test]
+    fn allow_overrides_deny() {
+        let exes = ["UnityCrashHandler64.exe","libunity.so","Hollow Knight.exe"];
+
+        let ranked = rank_names(&ExeFilters::default(),&exes);
+        assert_eq!(scores(&ranked),vec![("Hollow Knight.exe",60,false),("UnityCrashHandler64.exe",20,true),("libunity.so",20,true)]);
+        assert_eq!(ranked[1].reasons[0],"deny-listed by \"UnityCrashHandler*\"");
+        assert_eq!(ranked[2].reasons[0],"shared library");
+
+        let filters = ExeFilters {
+            allow: vec!["unitycrashhandler*".to_string()],
+            ..ExeFilters::default()
+        };
+
+        let ranked = rank_names(&filters,&exes);
+        assert_eq!(scores(&ranked)[0],("UnityCrashHandler64.exe",120,false));
+    }
+
+    #[test]
+    fn sorts_by_score_then_name() {
+        let ranked = rank_names(&ExeFilters::default(),&["b/zeta","b/alpha","crashpad_handler","HollowKnight"]);
+
+        assert_eq!(
+            ranked.iter().map(|exe| exe.name.as_str()).collect::<Vec<_>>(),
+            vec!["HollowKnight","alpha","zeta","crashpad_handler"]
+        );
+    }
+}
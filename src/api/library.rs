@@ -0,0 +1,255 @@
+use napi_derive::napi;
+
+#[napi]
+pub mod library {
+    use std::path::{Path,PathBuf};
+    use log::{debug,error};
+    use crate::api::vdf::{self,KeyValues};
+    use crate::api::steamdir::steamdir::resolve_root;
+
+    const STATE_FLAGS: [(u32,&str);22] = [
+        (1,"Invalid"),
+        (2,"Uninstalled"),
+        (4,"UpdateRequired"),
+        (8,"FullyInstalled"),
+        (16,"Encrypted"),
+        (32,"Locked"),
+        (64,"FilesMissing"),
+        (128,"AppRunning"),
+        (256,"FilesCorrupt"),
+        (512,"UpdateRunning"),
+        (1024,"UpdatePaused"),
+        (2048,"UpdateStarted"),
+        (4096,"Uninstalling"),
+        (8192,"BackupRunning"),
+        (65536,"Reconfiguring"),
+        (131072,"Validating"),
+        (262144,"AddingFiles"),
+        (524288,"Preallocating"),
+        (1048576,"Downloading"),
+        (2097152,"Staging"),
+        (4194304,"Committing"),
+        (8388608,"UpdateStopping")
+    ];
+
+    #[napi(object)]
+    pub struct InstalledGame {
+        pub app_id: u32,
+        pub name: String,
+        pub install_dir: String,
+        pub library_path: String,
+        pub size_on_disk: i64,
+        pub build_id: u32,
+        /// Unix timestamp of the last update
+        pub last_updated: u32,
+        pub state_flags: u32,
+        /// Names of the bits set in `stateFlags`, e.g. "FullyInstalled"
+        pub state: Vec<String>
+    }
+
+    /// Every library folder listed in "libraryfolders.vdf", always including the Steam root itself
+    pub(crate) fn library_folders(root: &Path) -> Vec<PathBuf> {
+        let mut folders = vec![root.to_path_buf()];
+
+        let manifest = [
+            root.join("steamapps/libraryfolders.vdf"),
+            root.join("config/libraryfolders.vdf")
+        ].into_iter().find(|path| path.is_file());
+
+        let Some(kv) = manifest.and_then(|path| vdf::read_file(&path)) else {
+            return folders
+        };
+
+        let Some(entries) = kv.get_object("libraryfolders") else {
+            error!("\"libraryfolders.vdf\" has no \"libraryfolders\" block");
+            return folders
+        };
+
+        for (key,value) in entries.iter() {
+            if key.parse::<u32>().is_err() {
+                continue
+            }
+
+            // Current format nests each folder in a block with a "path" key, older clients stored the path directly
+            let path = match value {
+                vdf::VdfValue::Object(folder) => folder.get_str("path"),
+                vdf::VdfValue::String(path) => Some(path.as_str())
+            };
+
+            if let Some(path) = path.map(PathBuf::from) {
+                if !folders.iter().any(|folder| same_path(folder,&path)) {
+                    folders.push(path);
+                }
+            }
+        }
+
+        folders
+    }
+
+    fn same_path(a: &Path,b: &Path) -> bool {
+        match (std::fs::canonicalize(a),std::fs::canonicalize(b)) {
+            (Ok(a),Ok(b)) => a == b,
+            _ => a == b
+        }
+    }
+
+    fn parse_number<T: std::str::FromStr + Default>(app: &KeyValues,key: &str) -> T {
+        app.get_str(key)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn read_app_manifest(library: &Path,manifest: &Path) -> Option<InstalledGame> {
+        let kv = vdf::read_file(manifest)?;
+        let app = kv.get_object("AppState")?;
+
+        let app_id = parse_number::<u32>(app,"appid");
+        let installdir = app.get_str("installdir").unwrap_or_default();
+
+        if app_id == 0 {
+            error!("\"{}\" has no valid appid",manifest.display());
+            return None
+        }
+
+        // An empty installdir would point at "steamapps/common" itself, i.e. every game in the library
+        if installdir.trim().is_empty() {
+            error!("\"{}\" has no installdir",manifest.display());
+            return None
+        }
+
+        let state_flags = parse_number::<u32>(app,"StateFlags");
+
+        Some(InstalledGame {
+            app_id,
+            name: app.get_str("name").unwrap_or_default().to_string(),
+            install_dir: library
+                .join("steamapps/common")
+                .join(installdir)
+                .to_string_lossy()
+                .to_string(),
+            library_path: library.to_string_lossy().to_string(),
+            size_on_disk: parse_number::<i64>(app,"SizeOnDisk"),
+            build_id: parse_number::<u32>(app,"buildid"),
+            last_updated: parse_number::<u32>(app,"LastUpdated"),
+            state_flags,
+            state: STATE_FLAGS
+                .iter()
+                .filter(|(flag,_)| state_flags & flag != 0)
+                .map(|(_,name)| name.to_string())
+                .collect()
+        })
+    }
+
+    fn app_manifests(library: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(library.join("steamapps")) else {
+            debug!("Library folder \"{}\" has no readable \"steamapps\" dir",library.display());
+            return Vec::new()
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("appmanifest_") && name.ends_with(".acf"))
+            })
+            .collect()
+    }
+
+    pub(crate) fn installed_games(root: &Path) -> Vec<InstalledGame> {
+        library_folders(root)
+            .iter()
+            .flat_map(|library| {
+                app_manifests(library)
+                    .into_iter()
+                    .filter_map(|manifest| read_app_manifest(library,&manifest))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Looks up a single app by reading only its own "appmanifest_<appid>.acf"
+    pub(crate) fn installed_game(root: &Path,app_id: u32) -> Option<InstalledGame> {
+        library_folders(root).iter().find_map(|library| {
+            let manifest = library.join(format!("steamapps/appmanifest_{}.acf",app_id));
+            manifest.is_file().then(|| read_app_manifest(library,&manifest)).flatten()
+        })
+    }
+
+    #[napi]
+    pub fn list_library_folders(steam_root: Option<String>) -> Vec<String> {
+        resolve_root(steam_root)
+            .map(|root| library_folders(&root))
+            .unwrap_or_default()
+            .iter()
+            .map(|folder| folder.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[napi]
+    pub fn list_installed_games(steam_root: Option<String>) -> Vec<InstalledGame> {
+        resolve_root(steam_root)
+            .map(|root| installed_games(&root))
+            .unwrap_or_default()
+    }
+
+    #[napi]
+    pub fn get_installed_game(app_id: u32,steam_root: Option<String>) -> Option<InstalledGame> {
+        resolve_root(steam_root).and_then(|root| installed_game(&root,app_id))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::api::testdir::TempDir;
+
+        fn manifest(library: &Path,app_id: u32,installdir: &str) {
+            std::fs::write(
+                library.join(format!("steamapps/appmanifest_{}.acf",app_id)),
+                format!(r#""AppState" {{
+                    "appid" "{}"
+                    "name" "Game {}"
+                    "installdir" "{}"
+                    "StateFlags" "4"
+                    "SizeOnDisk" "1234"
+                    "buildid" "56"
+                }}"#,app_id,app_id,installdir)
+            ).unwrap();
+        }
+
+        #[test]
+        fn reads_games_from_all_libraries() {
+            let root = TempDir::new("library");
+            let second = root.join("second");
+
+            std::fs::create_dir_all(root.join("steamapps")).unwrap();
+            std::fs::create_dir_all(second.join("steamapps")).unwrap();
+            std::fs::write(root.join("steamapps/libraryfolders.vdf"),format!(r#""libraryfolders" {{
+                "0" {{ "path" "{}" }}
+                "1" {{ "path" "{}" }}
+                "contentstatsid" "1"
+            }}"#,root.display(),second.display())).unwrap();
+
+            manifest(&root,10,"Counter-Strike");
+            manifest(&second,20,"Second Game");
+            manifest(&second,30,"");
+            std::fs::write(second.join("steamapps/notes.acf"),"ignored").unwrap();
+
+            assert_eq!(library_folders(&root),vec![root.to_path_buf(),second.clone()]);
+
+            let mut games = installed_games(&root);
+            games.sort_by_key(|game| game.app_id);
+
+            assert_eq!(games.iter().map(|game| game.app_id).collect::<Vec<_>>(),vec![10,20]);
+            assert_eq!(PathBuf::from(&games[1].install_dir),second.join("steamapps/common/Second Game"));
+            assert_eq!(games[1].library_path,second.to_string_lossy());
+            assert_eq!((games[1].size_on_disk,games[1].build_id),(1234,56));
+            assert_eq!(games[1].state,vec!["UpdateRequired".to_string()]);
+
+            assert_eq!(installed_game(&root,20).map(|game| game.name),Some("Game 20".to_string()));
+            assert!(installed_game(&root,30).is_none());
+            assert!(installed_game(&root,40).is_none());
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use glob::{Pattern,MatchOptions};
+use regex::Regex;
+
+/// A process that linked game patterns can be tested against
+pub trait LinkTarget {
+    fn matches_name(&self,name: &str) -> bool;
+    /// Every name the process goes by (process name, exe file name, Wine exe name)
+    fn names(&self) -> Vec<String>;
+    /// Every path the executable goes by (exe link, and for Wine/Proton the Windows and Linux paths of the ".exe")
+    fn paths(&self) -> Vec<String>;
+    /// The program as started. Unlike the full command line this leaves out the arguments, where wrappers
+    /// such as Steam's reaper or Proton's launcher carry the game's path.
+    fn argv0(&self) -> Option<String>;
+    fn cmdline(&self) -> String;
+}
+
+/// One entry of a linked game. Parsed from user input as:
+/// - "re:<regex>": matched against names, paths and the command line
+/// - anything containing `*` or `?`: a case-insensitive glob, matched against names, or against paths and argv[0]
+///   if it contains a separator. Wildcards don't match across separators, and `[...]` classes work within a glob.
+///   Brackets alone don't make a glob, so names like "Game [DX12].exe" match literally.
+/// - anything containing a separator: a full path, compared with the exe paths and argv[0]
+/// - anything else: an exe name, compared case-insensitively
+#[derive(Debug,Clone)]
+pub enum LinkPattern {
+    Name(String),
+    Path(String),
+    Glob(Pattern,bool),
+    Regex(Regex)
+}
+
+fn has_separator(value: &str) -> bool {
+    value.contains('/') || value.contains('\\')
+}
+
+/// Forward slashes and lowercase, so "C:\Games\Foo.exe" and "c:/games/foo.exe" compare equal
+fn normalise_path(path: &str) -> String {
+    path.replace('\\',"/").to_lowercase()
+}
+
+/// First argument of a command line string, unquoting it as Windows quotes paths with spaces
+fn first_arg(cmdline: &str) -> Option<String> {
+    let cmdline = cmdline.trim_start();
+
+    let arg = match cmdline.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next(),
+        None => cmdline.split_whitespace().next()
+    };
+
+    arg.filter(|arg| !arg.is_empty()).map(str::to_string)
+}
+
+impl LinkPattern {
+    pub fn parse(entry: &str) -> Result<Self,String> {
+        let entry = entry.trim();
+
+        if entry.is_empty() {
+            return Err("Linked game entries can't be empty".to_string())
+        }
+
+        if let Some(regex) = entry.strip_prefix("re:") {
+            return Regex::new(regex)
+                .map(LinkPattern::Regex)
+                .map_err(|err| format!("Invalid regex \"{}\": {}",regex,err))
+        }
+
+        if entry.contains(['*','?']) {
+            let glob = normalise_path(entry);
+
+            return Pattern::new(&glob)
+                .map(|pattern| LinkPattern::Glob(pattern,has_separator(entry)))
+                .map_err(|err| format!("Invalid glob \"{}\": {}",entry,err))
+        }
+
+        match has_separator(entry) {
+            true => Ok(LinkPattern::Path(normalise_path(entry))),
+            false => Ok(LinkPattern::Name(entry.to_string()))
+        }
+    }
+
+    pub fn matches(&self,target: &impl LinkTarget) -> bool {
+        match self {
+            LinkPattern::Name(name) => target.matches_name(name),
+            LinkPattern::Path(path) => {
+                target.paths()
+                    .into_iter()
+                    .chain(target.argv0())
+                    .any(|candidate| normalise_path(&candidate) == *path)
+            },
+            LinkPattern::Glob(pattern,is_path) => {
+                let options = MatchOptions {
+                    case_sensitive: false,
+                    require_literal_separator: true,
+                    ..MatchOptions::default()
+                };
+
+                let candidates = match is_path {
+                    true => {
+                        let mut paths = target.paths();
+                        paths.extend(target.argv0());
+                        paths
+                    },
+                    false => target.names()
+                };
+
+                candidates.iter().any(|candidate| pattern.matches_with(&normalise_path(candidate),options))
+            },
+            LinkPattern::Regex(regex) => {
+                target.names()
+                    .into_iter()
+                    .chain(target.paths())
+                    .chain(std::iter::once(target.cmdline()))
+                    .any(|candidate| regex.is_match(&candidate))
+            }
+        }
+    }
+}
+
+#[cfg(target_os="linux")]
+impl LinkTarget for crate::api::procfs::ProcRecord {
+    fn matches_name(&self,name: &str) -> bool {
+        crate::api::procfs::ProcRecord::matches_name(self,name)
+    }
+
+    fn names(&self) -> Vec<String> {
+        crate::api::procfs::ProcRecord::names(self)
+    }
+
+    fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        paths.extend(self.exe.as_ref().map(|exe| exe.to_string_lossy().to_string()));
+
+        if let Some(wine) = self.wine_exe() {
+            paths.push(wine.windows_path);
+            paths.extend(wine.linux_path.map(|path| path.to_string_lossy().to_string()));
+        }
+
+        paths
+    }
+
+    fn argv0(&self) -> Option<String> {
+        self.cmdline.first().cloned()
+    }
+
+    fn cmdline(&self) -> String {
+        self.command()
+    }
+}
+
+impl LinkTarget for crate::api::proctree::ProcessEntry {
+    fn matches_name(&self,name: &str) -> bool {
+        self.names().iter().any(|candidate| candidate.eq_ignore_ascii_case(name))
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        names.extend(self.exe.rsplit(['/','\\']).next().filter(|exe| !exe.is_empty() && *exe != self.name).map(str::to_string));
+        names
+    }
+
+    fn paths(&self) -> Vec<String> {
+        vec![self.exe.clone()]
+    }
+
+    fn argv0(&self) -> Option<String> {
+        first_arg(&self.cmdline)
+    }
+
+    fn cmdline(&self) -> String {
+        self.cmdline.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::proctree::ProcessEntry;
+
+    fn process(name: &str,exe: &str,cmdline: &str) -> ProcessEntry {
+        ProcessEntry {
+            pid: 1,
+            ppid: 0,
+            name: name.to_string(),
+            exe: exe.to_string(),
+            cmdline: cmdline.to_string(),
+            app_id: None,
+            start_time: 0
+        }
+    }
+
+    fn matches(entry: &str,target: &ProcessEntry) -> bool {
+        LinkPattern::parse(entry).unwrap().matches(target)
+    }
+
+    #[test]
+    fn matches_names_case_insensitively() {
+        let dolphin = process("Dolphin.exe","C:\\Emulators\\Dolphin\\Dolphin.exe","\"C:\\Emulators\\Dolphin\\Dolphin.exe\" -e game.iso");
+
+        assert!(matches("dolphin.exe",&dolphin));
+        assert!(!matches("dolphin",&dolphin));
+
+        let bracketed = process("Game [DX12].exe","C:\\Games\\Game\\Game [DX12].exe","");
+        assert!(matches("Game [DX12].exe",&bracketed));
+        assert!(matches("C:\\Games\\Game\\Game [DX12].exe",&bracketed));
+        assert!(!matches("Game [DX12].exe",&process("Game D.exe","","")));
+    }
+
+    #[test]
+    fn matches_full_paths_in_either_slash_style() {
+        let game = process("game","/opt/games/game/bin/game","/opt/games/game/bin/game --fullscreen");
+        let relaunched = process("game","","\"C:\\Games\\My Game\\Game.exe\" -windowed");
+
+        assert!(matches("/opt/games/game/bin/game",&game));
+        assert!(!matches("/opt/games/other/bin/game",&game));
+        assert!(matches("c:/emulators/dolphin/dolphin.exe",&process("Dolphin.exe","C:\\Emulators\\Dolphin\\Dolphin.exe","")));
+        assert!(matches("C:\\Games\\My Game\\Game.exe",&relaunched));
+    }
+
+    #[test]
+    fn ignores_wrappers_passing_the_path_as_an_argument() {
+        let game = "/home/user/.steam/steam/steamapps/common/Game/Game.exe";
+        let reaper = process("reaper","/home/user/.steam/steam/ubuntu12_32/reaper",&format!("reaper SteamLaunch AppId=123 -- proton waitforexitandrun {}",game));
+        let proton = process("python3","/usr/bin/python3.11",&format!("python3 /steam/proton waitforexitandrun {}",game));
+
+        for wrapper in [&reaper,&proton] {
+            assert!(!matches(game,wrapper));
+            assert!(!matches("/home/user/.steam/steam/steamapps/common/Game/*",wrapper));
+        }
+
+        assert!(matches("/home/user/.steam/steam/steamapps/common/Game/*",&process("Game.exe",game,game)));
+    }
+
+    #[test]
+    fn matches_globs_against_names_or_paths() {
+        let retroarch = process("retroarch","/usr/bin/retroarch","retroarch -L /usr/lib/libretro/snes9x_libretro.so Mario.sfc");
+
+        assert!(matches("retro*",&retroarch));
+        assert!(!matches("*snes9x*",&retroarch));
+        assert!(!matches("*/libretro/snes9x*",&retroarch));
+        assert!(matches("/usr/bin/*",&retroarch));
+        assert!(!matches("/usr/*",&retroarch));
+        assert!(matches("/usr/*/retro*",&retroarch));
+        assert!(matches("retro[a-z]rch*",&retroarch));
+    }
+
+    #[test]
+    fn matches_regexes_against_the_command_line() {
+        let pcsx2 = process("pcsx2-qt","/usr/bin/pcsx2-qt","pcsx2-qt -fullscreen /roms/ps2/Okami.iso");
+
+        assert!(matches(r"re:Okami\.iso$",&pcsx2));
+        assert!(!matches(r"re:^okami",&pcsx2));
+        assert!(LinkPattern::parse("re:(").is_err());
+    }
+}
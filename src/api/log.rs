@@ -4,14 +4,143 @@ use napi_derive::napi;
 pub mod log {
     use fern::Dispatch;
     use log::{LevelFilter,error};
-    use std::fs::File;
+    use napi::JsFunction;
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{ThreadsafeFunction,ThreadsafeFunctionCallMode,ErrorStrategy};
+    use std::io::Write;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicBool,Ordering};
+    use std::time::Duration;
+    use crate::api::logfile::{RotatingFile,Rotation,json_line};
+    use crate::api::crashreport;
+    use crate::api::logfilter;
+    use crate::api::logsubscribers;
+    use crate::api::watcher::WatchHandle;
+
+    const DEFAULT_MAX_SIZE: u32 = 10 * 1024 * 1024;
+    const DEFAULT_MAX_FILES: u32 = 5;
+
+    /// `log` is linked into this addon alone, so only `init_logger` ever installs a logger
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+    #[napi(object)]
+    #[derive(Default)]
+    pub struct LogOptions {
+        /// Rotate "rust.log" once it grows past this many bytes, 0 to only rotate by age. Defaults to 10 MiB.
+        pub max_size: Option<u32>,
+        /// Also rotate once the current file has been written to for this many hours
+        pub max_age_hours: Option<u32>,
+        /// Rotated files to keep, "rust.log.1" being the newest. Defaults to 5.
+        pub max_files: Option<u32>,
+        /// Keep writing to the previous session's "rust.log" after a session header, instead of rotating it out
+        pub append: Option<bool>,
+        /// "text" (the default) or "json" for one JSON object per line with timestamp, level, target, message,
+        /// file, line and thread
+        pub format: Option<String>
+    }
+
+    #[napi(object)]
+    pub struct LogFilter {
+        /// Module path the level applies to, along with everything below it, e.g. "steamworksjs::api::processes"
+        pub target: String,
+        pub level: String
+    }
+
+    #[napi(object)]
+    pub struct LogConfig {
+        /// Level for targets without a filter of their own
+        pub level: String,
+        pub filters: Vec<LogFilter>
+    }
+
+    #[napi(object)]
+    pub struct LogRecord {
+        /// Milliseconds since the Unix epoch
+        pub time: i64,
+        pub level: String,
+        pub target: String,
+        pub message: String
+    }
+
+    #[napi(object)]
+    pub struct CrashReport {
+        /// The report file, to attach to bug reports
+        pub path: String,
+        /// RFC 3339 local time of the panic
+        pub time: String,
+        /// Version of this module
+        pub version: String,
+        pub os: String,
+        pub thread: String,
+        pub location: String,
+        pub message: String,
+        pub app_id: Option<u32>,
+        pub steam_initialised: bool,
+        pub backtrace: String,
+        /// The last log lines before the panic, oldest first
+        pub recent_log: Vec<String>
+    }
+
+    fn parse_level(level: &str) -> Result<LevelFilter,Error> {
+        LevelFilter::from_str(level).map_err(|_| Error::from_reason(format!(
+            "Unknown log level \"{}\", expected one of \"off\", \"error\", \"warn\", \"info\", \"debug\" or \"trace\"",
+            level
+        )))
+    }
+
+    fn level_name(level: LevelFilter) -> String {
+        level.to_string().to_lowercase()
+    }
 
     #[napi]
-    pub fn init_logger(app_data: String) -> String {
+    pub fn init_logger(app_data: String,options: Option<LogOptions>) -> String {
+        // Installing a second logger would fail, so bail before opening the file rotates away the current session's log
+        if INSTALLED.load(Ordering::SeqCst) {
+            let message = "Failed to initialise \"rust.log\": the logger is already initialised";
+            eprintln!("{}",message);
+            return message.to_string()
+        }
+
         let logfile = std::path::Path::new(&app_data).join("rust.log");
+        let options = options.unwrap_or_default();
+        let append = options.append.unwrap_or(false);
+
+        let json = match options.format.as_deref() {
+            None | Some("text") => false,
+            Some("json") => true,
+            Some(format) => return format!("Unknown log format \"{}\", expected \"text\" or \"json\"",format)
+        };
+
+        let rotation = Rotation {
+            max_size: Some(options.max_size.unwrap_or(DEFAULT_MAX_SIZE) as u64).filter(|max_size| *max_size > 0),
+            max_age: options.max_age_hours.map(|hours| Duration::from_secs(hours as u64 * 3600)),
+            max_files: options.max_files.unwrap_or(DEFAULT_MAX_FILES)
+        };
+
+        let mut file = match RotatingFile::open(&logfile,rotation,append) {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("Failed to open \"rust.log\": {}",err);
+                return format!("Failed to open \"rust.log\": {}",err)
+            }
+        };
+
+        if append && !json {
+            let _ = writeln!(
+                file,
+                "\n===== Session started {} (steamworksjs {}, pid {}) =====\n",
+                chrono::Local::now().format("%d/%m/%Y %H:%M:%S"),
+                env!("CARGO_PKG_VERSION"),
+                std::process::id()
+            );
+        }
+
+        let file_output = Dispatch::new()
+            .format(move |out,message,record| {
+                if json {
+                    return out.finish(format_args!("{}",json_line(record,chrono::Local::now())))
+                }
 
-        let logger_config = Dispatch::new()
-            .format(|out,message,record| {
                 out.finish(format_args!(
                     "[{}]\n[{}]: {}\n{}\n",
                     chrono::Local::now().format("%d/%m/%Y %H:%M:%S"),
@@ -20,8 +149,16 @@ pub mod log {
                     message
                 ))
             })
-            .level(LevelFilter::Debug)
-            .chain(File::create(logfile).expect("Failed to create log file"))
+            .chain(Box::new(file) as Box<dyn Write + Send>);
+
+        // Subscribers get the unformatted record, so they're chained beside the file rather than after its format
+        let logger_config = Dispatch::new()
+            // Levels are checked per record, so they can be changed after the logger is installed
+            .level(LevelFilter::Trace)
+            .filter(logfilter::enabled)
+            .chain(file_output)
+            .chain(fern::Output::call(logsubscribers::dispatch))
+            .chain(fern::Output::call(crashreport::remember))
             .apply();
 
         if let Err(err) = logger_config {
@@ -29,6 +166,15 @@ pub mod log {
             return format!("Failed to initialise \"rust.log\": {}",err).to_string()
         }
 
+        INSTALLED.store(true,Ordering::SeqCst);
+        log::set_max_level(logfilter::current().max_level());
+        crashreport::set_dir(std::path::Path::new(&app_data).join("crashes"));
+
+        // The text header is written straight to the file, but a JSON log should stay one record per line
+        if append && json {
+            log::info!("Session started (steamworksjs {}, pid {})",env!("CARGO_PKG_VERSION"),std::process::id());
+        }
+
         std::panic::set_hook(Box::new(|panic_info| {
             let location = panic_info.location().map_or_else(
                 || "unknown location".to_string(),
@@ -44,13 +190,129 @@ pub mod log {
             };
 
             error!("Panic occurred at \"{}\": {}",location,payload);
+
+            let Some(dir) = crashreport::dir() else {
+                return
+            };
+
+            let report = crashreport::report(&location,payload,&std::backtrace::Backtrace::force_capture());
+
+            match crashreport::write(&dir,&report) {
+                Ok(path) => error!("Crash report written to \"{}\"",path.display()),
+                Err(err) => error!("Failed to write crash report: {}",err)
+            }
         }));
 
         "Initialised \"rust.log\" successfully".to_string()
     }
 
+    /// Sets the level for targets without a filter of their own. Takes effect immediately.
+    #[napi]
+    pub fn set_level(level: String) -> Result<(),Error> {
+        let level = parse_level(&level)?;
+        logfilter::update(|filters| filters.level = level);
+        Ok(())
+    }
+
+    /// Sets the level for `target` and the modules below it, e.g. `setFilter("steamworksjs::api::processes","warn")`
+    #[napi]
+    pub fn set_filter(target: String,level: String) -> Result<(),Error> {
+        let level = parse_level(&level)?;
+        logfilter::update(|filters| filters.set_target(&target,level));
+        Ok(())
+    }
+
+    /// Returns whether there was a filter for `target`
+    #[napi]
+    pub fn remove_filter(target: String) -> bool {
+        logfilter::update(|filters| filters.remove_target(&target))
+    }
+
     #[napi]
-    pub fn test_panic() {
-        panic!("This is a test panic");
+    pub fn get_config() -> LogConfig {
+        let filters = logfilter::current();
+
+        LogConfig {
+            level: level_name(filters.level),
+            filters: filters.targets
+                .into_iter()
+                .map(|(target,level)| LogFilter { target, level: level_name(level) })
+                .collect()
+        }
+    }
+
+    /// Calls `callback` with every record at `minLevel` or above (all by default) that passes `setLevel` and
+    /// `setFilter`, once `initLogger` has run. Records are queued for JS, so logging never waits on the callback.
+    #[napi]
+    pub fn subscribe(
+        #[napi(ts_arg_type = "(record: LogRecord) => void")] callback: JsFunction,
+        min_level: Option<String>
+    ) -> Result<WatchHandle,Error> {
+        let min_level = match min_level {
+            Some(level) => parse_level(&level)?,
+            None => LevelFilter::Trace
+        };
+
+        let threadsafe_callback: ThreadsafeFunction<LogRecord,ErrorStrategy::Fatal> = callback
+            .create_threadsafe_function(0,|ctx| Ok(vec![ctx.value]))?;
+
+        let id = logsubscribers::add(min_level,move |record| {
+            threadsafe_callback.call(LogRecord {
+                time: chrono::Utc::now().timestamp_millis(),
+                level: record.level().to_string().to_lowercase(),
+                target: record.target().to_string(),
+                message: record.args().to_string()
+            },ThreadsafeFunctionCallMode::NonBlocking);
+        });
+
+        // Removing the subscriber drops its callback, so it no longer keeps Node's event loop alive
+        Ok(WatchHandle::on_stop(move || logsubscribers::remove(id)))
+    }
+
+    /// The newest crash report in "<appData>/crashes", from this session or an earlier one
+    #[napi]
+    pub fn get_last_crash_report() -> Option<CrashReport> {
+        let (path,report) = crashreport::latest(&crashreport::dir()?)?;
+        let text = |key: &str| report[key].as_str().unwrap_or("").to_string();
+
+        Some(CrashReport {
+            path: path.to_string_lossy().to_string(),
+            time: text("time"),
+            version: text("version"),
+            os: text("os"),
+            thread: text("thread"),
+            location: text("location"),
+            message: text("message"),
+            app_id: report["appId"].as_u64().map(|app_id| app_id as u32),
+            steam_initialised: report["steamInitialised"].as_bool().unwrap_or(false),
+            backtrace: text("backtrace"),
+            recent_log: report["recentLog"]
+                .as_array()
+                .map(|lines| lines.iter().filter_map(|line| line.as_str().map(String::from)).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Panics on the calling thread. With `onThread`, panics on a "test-panic" thread instead and returns
+    /// the crash report it produced, so the report can be checked without taking the app down.
+    #[napi]
+    pub fn test_panic(on_thread: Option<bool>) -> Option<CrashReport> {
+        if !on_thread.unwrap_or(false) {
+            panic!("This is a test panic");
+        }
+
+        let panicked = std::thread::Builder::new()
+            .name("test-panic".to_string())
+            .spawn(|| panic!("This is a test panic"))
+            .map(|thread| thread.join().is_err());
+
+        match panicked {
+            Ok(true) => get_last_crash_report(),
+            Ok(false) => None,
+            Err(err) => {
+                error!("Failed to spawn \"test-panic\" thread: {}",err);
+                None
+            }
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,249 @@
+use std::fs::{File,OpenOptions};
+use std::io::{self,Write};
+use std::path::{Path,PathBuf};
+use std::time::{Duration,SystemTime};
+use chrono::{DateTime,Local,SecondsFormat};
+use log::Record;
+
+/// When `RotatingFile` rolls over, and how many old files it keeps
+#[derive(Debug,Clone,PartialEq)]
+pub struct Rotation {
+    /// Rotate once the file grows past this many bytes
+    pub max_size: Option<u64>,
+    /// Rotate once the file has been written to for this long
+    pub max_age: Option<Duration>,
+    /// Rotated files are kept as "<name>.1" (the newest) up to "<name>.<max_files>"
+    pub max_files: u32
+}
+
+/// A log file that moves itself to "<name>.1" (shifting older ones up) once it's too big or too old.
+/// Rotation is checked on flush, which fern does after every record, so records are never split across files.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    opened: SystemTime,
+    rotation: Rotation
+}
+
+fn rotated(path: &Path,index: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}",index));
+    path.with_file_name(name)
+}
+
+/// Moves `path` to "<path>.1", shifting the existing rotated files up and dropping the oldest
+fn shift(path: &Path,max_files: u32) -> io::Result<()> {
+    if max_files == 0 {
+        return std::fs::remove_file(path)
+    }
+
+    let _ = std::fs::remove_file(rotated(path,max_files));
+
+    for index in (1..max_files).rev() {
+        let from = rotated(path,index);
+
+        if from.exists() {
+            std::fs::rename(&from,rotated(path,index + 1))?;
+        }
+    }
+
+    std::fs::rename(path,rotated(path,1))
+}
+
+impl RotatingFile {
+    /// Opens `path` for writing. Unless `append` is set, a non-empty file left by a previous session
+    /// is rotated out of the way first rather than truncated.
+    pub fn open(path: impl Into<PathBuf>,rotation: Rotation,append: bool) -> io::Result<Self> {
+        let path = path.into();
+
+        if !append && path.metadata().is_ok_and(|metadata| metadata.len() > 0) {
+            shift(&path,rotation.max_files)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let size = metadata.len();
+
+        // An appended file keeps its age, so `max_age` still applies across sessions
+        let opened = [metadata.created(),metadata.modified()]
+            .into_iter()
+            .flatten()
+            .min()
+            .filter(|_| size > 0)
+            .unwrap_or_else(SystemTime::now);
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            opened,
+            rotation
+        })
+    }
+
+    fn due(&self) -> bool {
+        let too_big = self.rotation.max_size.is_some_and(|max_size| self.size > max_size);
+        let too_old = self.rotation.max_age.is_some_and(|max_age| self.opened.elapsed().unwrap_or_default() >= max_age);
+
+        self.size > 0 && (too_big || too_old)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        shift(&self.path,self.rotation.max_files)?;
+
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.size = 0;
+        self.opened = SystemTime::now();
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self,buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.due() {
+            self.rotate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// One JSON-lines log record. `thread` is the thread's name, or its id for unnamed threads.
+pub fn json_line(record: &Record,time: DateTime<Local>) -> String {
+    let thread = std::thread::current();
+
+    serde_json::json!({
+        "timestamp": time.to_rfc3339_opts(SecondsFormat::Millis,false),
+        "level": record.level().as_str().to_lowercase(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+        "file": record.file(),
+        "line": record.line(),
+        "thread": thread.name().map_or_else(|| format!("{:?}",thread.id()),String::from)
+    }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::testdir::TempDir;
+
+    fn read(path: PathBuf) -> String {
+        std::fs::read_to_string(path).unwrap_or_default()
+    }
+
+    fn record(file: &mut RotatingFile,line: &str) {
+        writeln!(file,"{}",line).unwrap();
+        file.flush().unwrap();
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = TempDir::new("logfile-size");
+        let path = dir.join("rust.log");
+        let rotation = Rotation { max_size: Some(10), max_age: None, max_files: 2 };
+        let mut file = RotatingFile::open(&path,rotation,false).unwrap();
+
+        record(&mut file,"first");
+        record(&mut file,"second record");
+        record(&mut file,"third record");
+        record(&mut file,"fourth");
+
+        assert_eq!(read(path.clone()),"fourth\n");
+        assert_eq!(read(rotated(&path,1)),"third record\n");
+        assert_eq!(read(rotated(&path,2)),"first\nsecond record\n");
+        assert!(!rotated(&path,3).exists());
+    }
+
+    #[test]
+    fn rotates_previous_session_unless_appending() {
+        let dir = TempDir::new("logfile-session");
+        let path = dir.join("rust.log");
+        let rotation = Rotation { max_size: None, max_age: None, max_files: 3 };
+
+        record(&mut RotatingFile::open(&path,rotation.clone(),false).unwrap(),"one");
+        record(&mut RotatingFile::open(&path,rotation.clone(),false).unwrap(),"two");
+
+        assert_eq!(read(path.clone()),"two\n");
+        assert_eq!(read(rotated(&path,1)),"one\n");
+
+        record(&mut RotatingFile::open(&path,rotation,true).unwrap(),"three");
+
+        assert_eq!(read(path.clone()),"two\nthree\n");
+        assert!(!rotated(&path,2).exists());
+    }
+
+    #[test]
+    fn formats_json_lines() {
+        use chrono::TimeZone;
+
+        let time = Local.with_ymd_and_hms(2024,3,9,14,5,30).unwrap();
+        let line = json_line(
+            &Record::builder()
+                .level(log::Level::Warn)
+                .target("steamworksjs::api::processes")
+                .file(Some("src/api/processes.rs"))
+                .line(Some(42))
+                .args(format_args!("Multi\nline \"quoted\""))
+                .build(),
+            time
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert!(!line.contains('\n'));
+        assert_eq!(json["timestamp"],time.to_rfc3339_opts(SecondsFormat::Millis,false));
+        assert_eq!(json["level"],"warn");
+        assert_eq!(json["target"],"steamworksjs::api::processes");
+        assert_eq!(json["message"],"Multi\nline \"quoted\"");
+        assert_eq!((json["file"].as_str(),json["line"].as_u64()),(Some("src/api/processes.rs"),Some(42)));
+        assert!(json["thread"].as_str().is_some_and(|thread| !thread.is_empty()));
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let dir = TempDir::new("logfile-age");
+        let path = dir.join("rust.log");
+        let rotation = Rotation { max_size: None, max_age: Some(Duration::ZERO), max_files: 1 };
+        let mut file = RotatingFile::open(&path,rotation,false).unwrap();
+
+        record(&mut file,"old");
+        record(&mut file,"new");
+
+        assert_eq!(read(path.clone()),"");
+        assert_eq!(read(rotated(&path,1)),"new\n");
+    }
+
+    #[test]
+    fn appending_keeps_file_age() {
+        let dir = TempDir::new("logfile-append-age");
+        let path = dir.join("rust.log");
+        let rotation = Rotation { max_size: None, max_age: Some(Duration::from_secs(3600)), max_files: 1 };
+
+        std::fs::write(&path,"yesterday\n").unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(24 * 3600))
+            .unwrap();
+
+        record(&mut RotatingFile::open(&path,rotation.clone(),true).unwrap(),"today");
+
+        assert_eq!(read(path.clone()),"");
+        assert_eq!(read(rotated(&path,1)),"yesterday\ntoday\n");
+
+        record(&mut RotatingFile::open(&path,rotation,true).unwrap(),"fresh");
+
+        assert_eq!(read(path.clone()),"fresh\n");
+    }
+}
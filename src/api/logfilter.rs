@@ -0,0 +1,101 @@
+use log::{LevelFilter,Metadata};
+use std::sync::RwLock;
+
+/// The default level plus per-target overrides, consulted for every record so they can change at runtime
+#[derive(Debug,Clone,PartialEq)]
+pub struct LogFilters {
+    pub level: LevelFilter,
+    /// Target prefixes such as "steamworksjs::api::processes", in the order they were added
+    pub targets: Vec<(String,LevelFilter)>
+}
+
+lazy_static! {
+    static ref FILTERS: RwLock<LogFilters> = RwLock::new(LogFilters::new(LevelFilter::Debug));
+}
+
+/// A filter applies to its own target and anything below it, so "a::b" covers "a::b::c" but not "a::bc"
+fn covers(filter: &str,target: &str) -> bool {
+    target.strip_prefix(filter).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+impl LogFilters {
+    pub fn new(level: LevelFilter) -> Self {
+        Self { level, targets: Vec::new() }
+    }
+
+    /// The most specific filter covering `target`, or the default level
+    pub fn level_for(&self,target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(filter,_)| covers(filter,target))
+            .max_by_key(|(filter,_)| filter.len())
+            .map_or(self.level,|(_,level)| *level)
+    }
+
+    pub fn set_target(&mut self,target: &str,level: LevelFilter) {
+        match self.targets.iter_mut().find(|(filter,_)| filter == target) {
+            Some(filter) => filter.1 = level,
+            None => self.targets.push((target.to_string(),level))
+        }
+    }
+
+    pub fn remove_target(&mut self,target: &str) -> bool {
+        let before = self.targets.len();
+        self.targets.retain(|(filter,_)| filter != target);
+        self.targets.len() != before
+    }
+
+    /// Most verbose level any target can log at, so records nothing would keep are skipped early
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets.iter().map(|(_,level)| *level).fold(self.level,|max,level| max.max(level))
+    }
+}
+
+pub fn enabled(metadata: &Metadata) -> bool {
+    metadata.level() <= FILTERS.read().unwrap().level_for(metadata.target())
+}
+
+pub fn current() -> LogFilters {
+    FILTERS.read().unwrap().clone()
+}
+
+/// Changes the filters and raises or lowers `log`'s global max level to match
+pub fn update<T>(change: impl FnOnce(&mut LogFilters) -> T) -> T {
+    let mut filters = FILTERS.write().unwrap();
+    let result = change(&mut filters);
+    log::set_max_level(filters.max_level());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_target_wins() {
+        let mut filters = LogFilters::new(LevelFilter::Debug);
+        filters.set_target("steamworksjs::api",LevelFilter::Info);
+        filters.set_target("steamworksjs::api::processes",LevelFilter::Warn);
+
+        assert_eq!(filters.level_for("steamworksjs::api::processes"),LevelFilter::Warn);
+        assert_eq!(filters.level_for("steamworksjs::api::processes::processes"),LevelFilter::Warn);
+        assert_eq!(filters.level_for("steamworksjs::api::procstats"),LevelFilter::Info);
+        assert_eq!(filters.level_for("steamworksjs::apix"),LevelFilter::Debug);
+        assert_eq!(filters.level_for("x11rb"),LevelFilter::Debug);
+    }
+
+    #[test]
+    fn tracks_max_level() {
+        let mut filters = LogFilters::new(LevelFilter::Warn);
+        assert_eq!(filters.max_level(),LevelFilter::Warn);
+
+        filters.set_target("steamworksjs::api::wininfo",LevelFilter::Trace);
+        filters.set_target("steamworksjs::api::wininfo",LevelFilter::Info);
+        assert_eq!(filters.targets.len(),1);
+        assert_eq!(filters.max_level(),LevelFilter::Info);
+
+        assert!(filters.remove_target("steamworksjs::api::wininfo"));
+        assert!(!filters.remove_target("steamworksjs::api::wininfo"));
+        assert_eq!(filters.max_level(),LevelFilter::Warn);
+    }
+}
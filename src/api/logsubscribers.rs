@@ -0,0 +1,77 @@
+use log::{LevelFilter,Record};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64,Ordering};
+
+type Callback = Box<dyn Fn(&Record) + Send + Sync>;
+
+struct Subscriber {
+    id: u64,
+    min_level: LevelFilter,
+    callback: Callback
+}
+
+lazy_static! {
+    static ref SUBSCRIBERS: RwLock<Vec<Subscriber>> = RwLock::new(Vec::new());
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Registers `callback` for records at `min_level` or above, returning the id to `remove` it with
+pub fn add(min_level: LevelFilter,callback: impl Fn(&Record) + Send + Sync + 'static) -> u64 {
+    let id = NEXT_ID.fetch_add(1,Ordering::SeqCst);
+
+    SUBSCRIBERS.write().unwrap().push(Subscriber {
+        id,
+        min_level,
+        callback: Box::new(callback)
+    });
+
+    id
+}
+
+/// Drops the subscriber and its callback straight away
+pub fn remove(id: u64) {
+    SUBSCRIBERS.write().unwrap().retain(|subscriber| subscriber.id != id);
+}
+
+/// The logger's fan-out sink
+pub fn dispatch(record: &Record) {
+    for subscriber in SUBSCRIBERS.read().unwrap().iter() {
+        if record.level() <= subscriber.min_level {
+            (subscriber.callback)(record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+    use std::sync::{Arc,Mutex};
+
+    fn send(level: Level,message: &str) {
+        dispatch(&Record::builder().level(level).target("test").args(format_args!("{}",message)).build());
+    }
+
+    #[test]
+    fn delivers_by_level_until_removed() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+
+        let id = add(LevelFilter::Info,move |record| {
+            sink.lock().unwrap().push(format!("{} {}",record.level(),record.args()));
+        });
+
+        send(Level::Debug,"hidden");
+        send(Level::Warn,"shown");
+        remove(id);
+
+        // Released on removal, without waiting for another record
+        assert_eq!(Arc::strong_count(&received),1);
+
+        send(Level::Error,"after stop");
+
+        assert_eq!(*received.lock().unwrap(),vec!["WARN shown".to_string()]);
+        assert!(SUBSCRIBERS.read().unwrap().iter().all(|subscriber| subscriber.id != id));
+    }
+}
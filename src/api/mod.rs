@@ -6,4 +6,34 @@ pub mod log;
 pub mod screenshots;
 pub mod utils;
 pub mod processes;
-pub mod wininfo;
\ No newline at end of file
+pub mod wininfo;
+pub mod vdf;
+pub mod steamdir;
+pub mod library;
+pub mod accounts;
+pub mod watcher;
+pub mod runningapp;
+pub mod proctree;
+pub mod procexit;
+pub mod exerank;
+pub mod exeindex;
+pub mod appinfo;
+pub mod linkmatch;
+pub mod procstats;
+pub mod logfile;
+pub mod logfilter;
+pub mod logsubscribers;
+pub mod crashreport;
+
+#[cfg(target_os="linux")]
+pub mod procfs;
+#[cfg(target_os="linux")]
+pub mod x11;
+#[cfg(target_os="linux")]
+pub mod wayland;
+#[cfg(target_os="windows")]
+pub mod win32window;
+#[cfg(target_os="windows")]
+pub mod win32process;
+#[cfg(test)]
+pub mod testdir;
\ No newline at end of file
@@ -6,20 +6,33 @@ pub mod win32 {
     pub use std::os::windows::process::CommandExt;
     pub const CREATENOWINDOW: u32 = 0x08000000;
     pub use winreg::RegKey;
-    pub use winreg::enums::HKEY_CURRENT_USER;
+    pub use winreg::enums::{HKEY_CURRENT_USER,HKEY_LOCAL_MACHINE};
 }
 
 #[napi]
 pub mod processes {
-    use log::{info,error};
+    use log::{debug,info,error};
+    use napi::JsFunction;
+    use napi::bindgen_prelude::{Either,Error};
+    use napi::threadsafe_function::{ThreadsafeFunction,ThreadsafeFunctionCallMode,ErrorStrategy};
+    use std::time::Duration;
+    use crate::api::watcher::WatchHandle;
+    use crate::api::proctree::{snapshot,ProcessEntry,ProcessTree,TreeChange,TreeRoot};
+
+    use std::path::{Path,PathBuf};
+    use crate::api::exerank::{self,ExeFilters,RankedExe};
+    use crate::api::exeindex;
+    use crate::api::linkmatch::LinkPattern;
+
+    const DEFAULT_INTERVAL_MS: u32 = 1000;
+    const EXIT_WAIT_SLICE_MS: u64 = 250;
 
     static REGEX: &str = r#"^(.+?)\s+(\d+)\s+(.+)$"#;
 
     #[allow(unused_mut)]
-    fn get_install_dir_exes(input: String) -> Vec<String> {
+    fn get_install_dir_exes(input: String) -> Vec<PathBuf> {
         use glob::glob;
         use regex::Regex;
-        use std::path::Path;
 
         let mut install_dir_exes = Vec::new();
 
@@ -42,34 +55,47 @@ pub mod processes {
             .to_string())
             .unwrap_or_else(|| input);
 
-        let dir = Path::new(&executable_path)
-            .to_str()
-            .unwrap()
-            .to_string();
+        // Install dirs can contain "[", "*" or "?", which would otherwise be read as part of the pattern
+        let pattern = format!("{}/**/*{}",glob::Pattern::escape(&executable_path),ext);
 
-        let pattern = format!("{}/**/*{}",dir,ext);
+        let entries = match glob(&pattern) {
+            Ok(entries) => entries,
+            Err(err) => {
+                error!("Invalid install dir pattern \"{}\": {}",pattern,err);
+                return install_dir_exes
+            }
+        };
 
-        for entry in glob(&pattern).expect("Failed to read glob pattern") {
+        for entry in entries {
             match entry {
                 Ok(file) => {
                     #[cfg(target_os="linux")] {
                         use std::os::unix::fs::PermissionsExt;
 
-                        let metadata = file.metadata().expect("Failed to get file metadata");
+                        // Broken symlinks have no metadata to check
+                        let metadata = match file.metadata() {
+                            Ok(metadata) => metadata,
+                            Err(err) => {
+                                debug!("Skipping \"{}\": {}",file.display(),err);
+                                continue
+                            }
+                        };
 
                         if let Some(file_name) = file.file_name() {
                             let file_name = file_name.to_string_lossy().to_string();
-                            let has_valid_ext = file_name.find(".").is_none() || file_name.ends_with(".sh") || file_name.ends_with(".so") || file_name.ends_with(".exe");
-                            let is_valid = file.is_file() && metadata.permissions().mode() & 0o111 != 0 && has_valid_ext;
+                            let has_valid_ext = file_name.find(".").is_none() || file_name.ends_with(".sh") || file_name.ends_with(".so") || file_name.to_lowercase().ends_with(".exe");
+                            // Proton games ship Windows ".exe"s that often lack the executable bit
+                            let is_executable = metadata.permissions().mode() & 0o111 != 0 || file_name.to_lowercase().ends_with(".exe");
+                            let is_valid = file.is_file() && is_executable && has_valid_ext;
         
                             if is_valid {
-                                install_dir_exes.push(file_name);
+                                install_dir_exes.push(file);
                             }
                         }
                     }
 
                     #[cfg(target_os="windows")]
-                    install_dir_exes.push(file.file_name().expect("Failed to get file name").to_string_lossy().to_string())
+                    install_dir_exes.push(file)
                 },
                 Err(err) => error!("Error while iterating over dir entries: {}",err)
             }
@@ -78,146 +104,503 @@ pub mod processes {
         install_dir_exes
     }
     
-    fn get_game_exes(appid: u32) -> Vec<String> {
+    fn get_ranked_exes(installdir: String) -> Vec<RankedExe> {
+        let exes = get_install_dir_exes(installdir.clone());
+        exerank::rank(Path::new(&installdir),&exes)
+    }
+
+    /// Install dir and build id, from the app manifest where possible so this works before Steam is initialised
+    fn get_app_install(appid: u32) -> Option<(String,u32)> {
         use steamworks::AppId;
-    
+        use crate::api::steamdir::steamdir::active_root;
+        use crate::api::library::library::installed_game;
+
+        if let Some(game) = active_root().and_then(|root| installed_game(&root,appid)) {
+            return Some((game.install_dir,game.build_id))
+        }
+
+        if !crate::client::has_client() {
+            return None
+        }
+
         let client = crate::client::get_client();
         let installdir = client.apps().app_install_dir(AppId::from(appid));
 
-        get_install_dir_exes(installdir)
+        (!installdir.is_empty()).then_some((installdir,0))
+    }
+
+    fn get_indexed_exes(appid: u32) -> Option<(String,Vec<PathBuf>)> {
+        let (installdir,build_id) = get_app_install(appid)?;
+        let exes = exeindex::exes(appid,&installdir,build_id,|| get_install_dir_exes(installdir.clone()));
+
+        Some((installdir,exes))
+    }
+
+    fn get_app_ranked_exes(appid: u32) -> Vec<RankedExe> {
+        match get_indexed_exes(appid) {
+            Some((installdir,exes)) => exerank::rank(Path::new(&installdir),&exes),
+            None => {
+                error!("Unable to find install dir for AppId {}",appid);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Executables Steam itself launches for the app, per appinfo.vdf. Proton runs the Windows entries on Linux.
+    fn get_launch_exes(appid: u32) -> Vec<String> {
+        use crate::api::appinfo::appinfo::{get_launch_configs,current_os};
+
+        let mut names: Vec<String> = Vec::new();
+
+        for config in get_launch_configs(appid,Some(true),None) {
+            let applies = config.applies_to(current_os()) || (cfg!(target_os="linux") && config.applies_to("windows"));
+            let name = config.exe_name().to_string();
+
+            if applies && !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    fn get_game_exes(appid: u32) -> Vec<String> {
+        let mut names = get_launch_exes(appid);
+
+        if !names.is_empty() {
+            info!("Launch executables for AppId {}: {:?}",appid,names);
+        }
+
+        for exe in get_app_ranked_exes(appid) {
+            if exe.excluded {
+                debug!("Ignoring \"{}\": {}",exe.path,exe.reasons.join(", "));
+            } else if !names.contains(&exe.name) {
+                names.push(exe.name);
+            }
+        }
+
+        names
+    }
+
+    /// Every executable under `install_dir`, best game candidate first, with the reasons for each score
+    #[napi]
+    pub fn rank_install_dir_exes(install_dir: String) -> Vec<RankedExe> {
+        get_ranked_exes(install_dir)
+    }
+
+    #[napi]
+    pub fn rank_game_exes(appid: u32) -> Vec<RankedExe> {
+        get_app_ranked_exes(appid)
+    }
+
+    /// Indexes the app's install dir on a background thread, so the first process lookup doesn't have to walk it.
+    /// The index is reused until the app is updated or its dirs change.
+    #[napi]
+    pub fn prebuild_exe_index(appid: u32) {
+        let spawned = std::thread::Builder::new()
+            .name(format!("exeindex-{}",appid))
+            .spawn(move || {
+                if get_indexed_exes(appid).is_none() {
+                    error!("Unable to prebuild exe index for AppId {}: install dir not found",appid);
+                }
+            });
+
+        if let Err(err) = spawned {
+            error!("Failed to spawn exe index thread: {}",err);
+        }
+    }
+
+    /// Where exe indexes are persisted. Defaults to "steamworksjs/exeindex" under the user's cache dir ("~/.cache" or "%LOCALAPPDATA%").
+    #[napi]
+    pub fn set_exe_index_dir(dir: String) {
+        exeindex::set_dir(PathBuf::from(dir));
+    }
+
+    /// Removes the index for `appid`, or every index if omitted
+    #[napi]
+    pub fn clear_exe_index(appid: Option<u32>) {
+        exeindex::clear(appid);
+    }
+
+    #[napi]
+    pub fn get_exe_filters() -> ExeFilters {
+        exerank::filters()
+    }
+
+    /// Replaces the deny/allow lists used when ranking exes. Extend the result of `getExeFilters()` to keep the defaults.
+    #[napi]
+    pub fn set_exe_filters(filters: ExeFilters) {
+        exerank::set_filters(filters);
+    }
+
+    #[napi]
+    pub fn reset_exe_filters() {
+        exerank::set_filters(ExeFilters::default());
     }
     
     #[napi(object)]
     pub struct ProcessInfo {
         pub pid: u32,
-        pub exe: String
+        pub exe: String,
+        /// Windows exe name for games running under Wine/Proton, e.g. "Game.exe"
+        pub windows_exe: Option<String>,
+        /// Linux path of the executable. For Wine/Proton games this is the resolved ".exe", not the Wine loader
+        pub linux_path: Option<String>
     }
 
-    #[allow(unused)]
-    #[napi]
-    pub fn get_game_processes(appid: u32,linkedgame: Option<String>) -> Vec<ProcessInfo> {
-        use std::process::Command;
-        use serde_json::{from_str,Value,Error};
-
+    /// Uses the same Toolhelp32 snapshot as tree tracking, so both agree on pids and exe paths
+    #[cfg(target_os="windows")]
+    fn find_processes(exes: &[String]) -> Vec<ProcessInfo> {
+        let snapshot = snapshot();
         let mut processes = Vec::new();
 
-        let mut exes = match linkedgame {
-            Some(game) => vec![game],
-            None => get_game_exes(appid)
+        for exename in exes {
+            for process in snapshot.iter().filter(|process| process.name.eq_ignore_ascii_case(exename)) {
+                info!("ProcessName: {}, ProcessId: {}, ExecutablePath: {}",exename,process.pid,process.exe);
+
+                processes.push(ProcessInfo {
+                    pid: process.pid,
+                    exe: process.exe.clone(),
+                    windows_exe: None,
+                    linux_path: None
+                });
+            }
+        }
+
+        processes
+    }
+
+    #[cfg(target_os="linux")]
+    fn process_info(process: &crate::api::procfs::ProcRecord) -> ProcessInfo {
+        let wine = process.wine_exe();
+
+        let linux_path = match &wine {
+            Some(wine) => wine.linux_path.clone(),
+            None => process.exe.clone()
         };
 
-        if cfg!(target_os="windows") {
-            exes.push("SAM.Game.exe".to_string());
+        ProcessInfo {
+            pid: process.pid,
+            exe: process.command(),
+            windows_exe: wine.map(|wine| wine.name),
+            linux_path: linux_path.map(|path| path.to_string_lossy().to_string())
         }
+    }
 
-        let output: std::process::Output;
-        let cmd = if cfg!(target_os="windows") {
-            "Get-CimInstance Win32_Process | Select ProcessName, ProcessId, ExecutablePath | ConvertTo-Json"
-        } else if cfg!(target_os="linux") {
-            "ps -eo comm,pid,cmd --no-headers"
-        } else {
-            "Unsupported platform"
+    #[cfg(target_os="linux")]
+    fn find_processes(exes: &[String]) -> Vec<ProcessInfo> {
+        use crate::api::procfs::ProcFs;
+
+        ProcFs::system()
+            .find_by_name(exes)
+            .into_iter()
+            .map(|(exename,process)| {
+                let info = process_info(&process);
+
+                info!("ProcessName: {}, ProcessId: {}, ExecutablePath: {}, WindowsExe: {:?}",exename,info.pid,info.exe,info.windows_exe);
+
+                info
+            })
+            .collect()
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn find_processes(_exes: &[String]) -> Vec<ProcessInfo> {
+        error!("Process lookup is not supported on this platform");
+        Vec::new()
+    }
+
+    #[cfg(target_os="windows")]
+    fn find_linked_processes(patterns: &[LinkPattern]) -> Vec<ProcessInfo> {
+        crate::api::proctree::snapshot()
+            .into_iter()
+            .filter(|process| patterns.iter().any(|pattern| pattern.matches(process)))
+            .map(|process| {
+                info!("Linked process: {}, ProcessId: {}, CommandLine: {}",process.name,process.pid,process.cmdline);
+
+                ProcessInfo {
+                    pid: process.pid,
+                    exe: process.exe,
+                    windows_exe: None,
+                    linux_path: None
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(target_os="linux")]
+    fn find_linked_processes(patterns: &[LinkPattern]) -> Vec<ProcessInfo> {
+        use crate::api::procfs::ProcFs;
+
+        ProcFs::system()
+            .processes()
+            .iter()
+            .filter(|process| patterns.iter().any(|pattern| pattern.matches(*process)))
+            .map(|process| {
+                let info = process_info(process);
+
+                info!("Linked process: {}, ProcessId: {}, CommandLine: {}",process.comm,info.pid,info.exe);
+
+                info
+            })
+            .collect()
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn find_linked_processes(_patterns: &[LinkPattern]) -> Vec<ProcessInfo> {
+        error!("Process lookup is not supported on this platform");
+        Vec::new()
+    }
+
+    /// Finds the game's processes from its install dir. `linkedgame` replaces that lookup with one or more entries,
+    /// each an exe name, a full path, a glob (any entry with `*` or `?`, e.g. "Dolphin*.exe") or a "re:" prefixed regex.
+    /// Paths and globs are matched against the process' exe path and argv[0], so launch wrappers aren't; regexes also
+    /// see the command line.
+    #[napi]
+    pub fn get_game_processes(appid: u32,linkedgame: Option<Either<String,Vec<String>>>) -> Result<Vec<ProcessInfo>,Error> {
+        let linked = match linkedgame {
+            Some(Either::A(entry)) => vec![entry],
+            Some(Either::B(entries)) => entries,
+            None => Vec::new()
         };
 
-        #[cfg(target_os="windows")] {
-            use super::win32::{CommandExt,CREATENOWINDOW};
+        if linked.is_empty() {
+            let mut exes = get_game_exes(appid);
+
+            if cfg!(target_os="windows") {
+                exes.push("SAM.Game.exe".to_string());
+            }
+
+            return Ok(find_processes(&exes))
+        }
+
+        let mut patterns = linked
+            .iter()
+            .map(|entry| LinkPattern::parse(entry))
+            .collect::<Result<Vec<_>,_>>()
+            .map_err(Error::from_reason)?;
 
-            output = Command::new("powershell")
-                .creation_flags(CREATENOWINDOW)
-                .args(["-Command",cmd])
-                .output()
-                .expect("Failed to run process list command");
+        if cfg!(target_os="windows") {
+            patterns.push(LinkPattern::Name("SAM.Game.exe".to_string()));
         }
 
+        Ok(find_linked_processes(&patterns))
+    }
+
+    /// Every process Steam launched for `appid` (launchers, the game and their children), found via the
+    /// `SteamAppId` environment variable. Other processes' environments can't be read on Windows, so it
+    /// falls back to matching the install dir's exe names there.
+    #[napi]
+    pub fn get_app_processes(appid: u32) -> Vec<ProcessInfo> {
         #[cfg(target_os="linux")] {
-            output = Command::new("sh")
-                .args(["-c",cmd])
-                .output()
-                .expect("Failed to execute \"ps\" command");
+            use crate::api::procfs::ProcFs;
+
+            let processes = ProcFs::system()
+                .find_by_app_id(appid)
+                .iter()
+                .map(process_info)
+                .collect::<Vec<_>>();
+
+            info!("Found {} process(es) for AppId {}: {:?}",processes.len(),appid,processes.iter().map(|p| p.pid).collect::<Vec<_>>());
+            processes
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        #[cfg(not(target_os="linux"))] {
+            get_game_processes(appid,None).unwrap_or_default()
+        }
+    }
 
-        let json: Result<Value,Error>;
+    #[napi(object)]
+    pub struct ProcessNode {
+        pub pid: u32,
+        pub ppid: u32,
+        pub name: String,
+        pub exe: String,
+        /// 0 for processes whose parent is outside the tree
+        pub depth: u32
+    }
 
-        #[cfg(target_os="windows")] {
-            json = from_str(&stdout);
+    #[napi(object)]
+    pub struct ProcessTreeChange {
+        /// "join" or "leave"
+        pub kind: String,
+        pub process: ProcessNode
+    }
+
+    fn process_node(tree: &ProcessTree,process: &ProcessEntry) -> ProcessNode {
+        ProcessNode {
+            pid: process.pid,
+            ppid: process.ppid,
+            name: process.name.clone(),
+            exe: process.exe.clone(),
+            depth: tree.depth(process.pid)
         }
-        
-        #[cfg(target_os="linux")] {
-            use regex::Regex;
+    }
+
+    fn app_root(appid: u32) -> TreeRoot {
+        // Linux identifies app processes by environment, elsewhere they're matched by exe name
+        let exes = if cfg!(target_os="linux") {
+            Vec::new()
+        } else {
+            get_game_exes(appid)
+        };
 
-            let mut json_output = Vec::new();
-            let regex = Regex::new(REGEX).expect("Failed to create Regex");
+        TreeRoot::App { app_id: appid, exes }
+    }
 
-            for line in stdout.lines() {
-                if let Some(captures) = regex.captures(line) {
-                    let process_name = captures.get(1).map_or("<unknown>",|m| m.as_str().trim());
-                    let process_id = captures.get(2).map_or("<unknown>",|m| m.as_str().trim());
-                    let executable_path = captures.get(3).map_or("<unknown>",|m| m.as_str().trim());
+    fn process_tree(root: TreeRoot) -> Vec<ProcessNode> {
+        let mut tree = ProcessTree::default();
+        tree.update(&snapshot(),&root);
+
+        tree.members()
+            .iter()
+            .map(|process| process_node(&tree,process))
+            .collect()
+    }
 
-                    let json_obj = serde_json::json!({
-                        "ProcessName": process_name,
-                        "ProcessId": process_id,
-                        "ExecutablePath": executable_path
-                    });
+    fn watch_tree(root: TreeRoot,handler: JsFunction,interval_ms: Option<u32>) -> Result<WatchHandle,Error> {
+        let threadsafe_handler: ThreadsafeFunction<ProcessTreeChange,ErrorStrategy::Fatal> = handler
+            .create_threadsafe_function(0,|ctx| Ok(vec![ctx.value]))?;
 
-                    json_output.push(json_obj);
+        let mut tree = ProcessTree::default();
+
+        Ok(WatchHandle::spawn(
+            "processtree-watcher",
+            Duration::from_millis(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS) as u64),
+            move || {
+                for change in tree.update(&snapshot(),&root) {
+                    let (kind,process) = match &change {
+                        TreeChange::Join(process) => ("join",process),
+                        TreeChange::Leave(process) => ("leave",process)
+                    };
+
+                    info!("Process {} {} tree: {} ({})",process.pid,if kind == "join" { "joined" } else { "left" },process.name,process.exe);
+
+                    threadsafe_handler.call(ProcessTreeChange {
+                        kind: kind.to_string(),
+                        process: process_node(&tree,process)
+                    },ThreadsafeFunctionCallMode::NonBlocking);
+                }
+
+                // A pid-rooted tree is finished once every member has exited, an app may be launched again
+                match root {
+                    TreeRoot::Pid(_) => !tree.members().is_empty(),
+                    TreeRoot::App { .. } => true
                 }
             }
+        ))
+    }
 
-            let json_array = Value::Array(json_output).to_string();
+    /// `pid` and all of its descendants
+    #[napi]
+    pub fn get_process_tree(pid: u32) -> Vec<ProcessNode> {
+        process_tree(TreeRoot::Pid(pid))
+    }
 
-            json = from_str(&json_array)
-        }
+    /// All processes launched for `appid` and their descendants
+    #[napi]
+    pub fn get_app_process_tree(appid: u32) -> Vec<ProcessNode> {
+        process_tree(app_root(appid))
+    }
 
-        for exename in exes {
-            match &json {
-                Ok(value) => {
-                    let stdoutprocesses = value
-                        .as_array()
-                        .expect("\"json\" is not an array")
-                        .iter()
-                        .filter(|p| {
-                            if let Some(pname) = p["ProcessName"].as_str() {
-                                exename.to_lowercase() == pname.to_lowercase()
-                            } else {
-                                false
-                            }
-                        })
-                        .collect::<Vec<_>>();
-    
-                    for process in stdoutprocesses {
-                        let pid = if cfg!(target_os="windows") {
-                            process["ProcessId"]
-                                .as_u64()
-                                .unwrap_or(0) as u32
-                        } else {
-                            process["ProcessId"]
-                                .as_str()
-                                .unwrap_or("0")
-                                .parse::<u32>()
-                                .unwrap_or(0) as u32
-                        };
-    
-                        let exe = process["ExecutablePath"]
-                            .as_str()
-                            .unwrap_or("")
-                            .to_string();
+    /// Calls `handler` as processes join or leave the tree under `pid`. Members are kept until they exit,
+    /// even if reparented, and the watcher stops once the whole tree has exited.
+    #[napi]
+    pub fn watch_process_tree(
+        pid: u32,
+        #[napi(ts_arg_type = "(change: ProcessTreeChange) => void")] handler: JsFunction,
+        interval_ms: Option<u32>
+    ) -> Result<WatchHandle,Error> {
+        watch_tree(TreeRoot::Pid(pid),handler,interval_ms)
+    }
 
-                        info!("ProcessName: {}, ProcessId: {}, ExecutablePath: {}",exename,pid,exe);
+    /// Like `watchProcessTree`, but rooted at every process launched for `appid`, so a game restarting itself
+    /// or being relaunched is picked up. Runs until stopped.
+    #[napi]
+    pub fn watch_app_process_tree(
+        appid: u32,
+        #[napi(ts_arg_type = "(change: ProcessTreeChange) => void")] handler: JsFunction,
+        interval_ms: Option<u32>
+    ) -> Result<WatchHandle,Error> {
+        watch_tree(app_root(appid),handler,interval_ms)
+    }
 
-                        processes.push(ProcessInfo {
-                            pid,
-                            exe
-                        });
-                    }
-                }
-                Err(err) => error!("No running process found for {}: {}",exename,err),
+    #[napi(object)]
+    pub struct ProcessExit {
+        pub pid: u32,
+        /// Milliseconds since the Unix epoch, usable with `new Date()`
+        pub exit_time: i64,
+        /// How the exit was detected: "pidfd", "handle", "poll", or "none" if the process had already exited
+        pub method: String
+    }
+
+    /// Calls `handler` exactly once when `pid` exits, without polling where the platform supports it.
+    /// Calls back straight away if the process isn't running.
+    #[napi]
+    pub fn watch_exit(
+        pid: u32,
+        #[napi(ts_arg_type = "(exit: ProcessExit) => void")] handler: JsFunction
+    ) -> Result<WatchHandle,Error> {
+        use crate::api::procexit::ExitWaiter;
+
+        let threadsafe_handler: ThreadsafeFunction<ProcessExit,ErrorStrategy::Fatal> = handler
+            .create_threadsafe_function(0,|ctx| Ok(vec![ctx.value]))?;
+
+        let waiter = ExitWaiter::new(pid);
+        info!("Watching process {} for exit using {}",pid,waiter.method());
+
+        // Waits in short slices so `stop()` is noticed promptly
+        Ok(WatchHandle::spawn("exit-watcher",Duration::ZERO,move || {
+            if !waiter.wait(Duration::from_millis(EXIT_WAIT_SLICE_MS)) {
+                return true
             }
-        }
 
-        processes
+            info!("Process {} exited",pid);
+
+            threadsafe_handler.call(ProcessExit {
+                pid,
+                exit_time: chrono::Utc::now().timestamp_millis(),
+                method: waiter.method().to_string()
+            },ThreadsafeFunctionCallMode::NonBlocking);
+
+            false
+        }))
+    }
+
+    #[napi(object)]
+    pub struct ProcessStats {
+        pub pid: u32,
+        /// User plus system CPU time, in milliseconds
+        pub cpu_time: f64,
+        /// CPU usage since the previous `getStats` call for this pid, where 100 is one fully busy core.
+        /// Missing on the first call.
+        pub cpu_percent: Option<f64>,
+        /// Resident set size (working set on Windows), in bytes
+        pub rss: f64,
+        pub threads: u32,
+        /// Milliseconds since the Unix epoch, usable with `new Date()`
+        pub start_time: i64,
+        /// Scheduler state on Linux, e.g. "R" (running), "S" (sleeping), "D" (uninterruptible) or "Z" (zombie)
+        pub state: Option<String>
+    }
+
+    /// Samples a process' resource usage. Call it periodically for idle/hang detection, as `cpuPercent` is
+    /// measured against the previous call.
+    #[napi]
+    pub fn get_stats(pid: u32) -> Option<ProcessStats> {
+        use crate::api::procstats;
+
+        let sample = procstats::sample(pid)?;
+
+        Some(ProcessStats {
+            pid,
+            cpu_time: sample.cpu_time_ms,
+            cpu_percent: procstats::cpu_percent(pid,&sample),
+            rss: sample.rss_bytes as f64,
+            threads: sample.threads,
+            start_time: sample.start_time_ms,
+            state: sample.state
+        })
     }
 
     #[napi]
@@ -234,4 +617,26 @@ pub mod processes {
             None => "".to_string()
         }
     }
+
+    #[cfg(all(test,target_os="linux"))]
+    mod tests {
+        use super::*;
+        use crate::api::testdir::TempDir;
+        use std::os::unix::fs::PermissionsExt;
+
+        #[test]
+        fn globs_install_dirs_with_pattern_characters() {
+            let temp = TempDir::new("processes-glob");
+            let dir = temp.join("Game [DX12] *?");
+            std::fs::create_dir_all(dir.join("bin")).unwrap();
+
+            let exe = dir.join("bin/game");
+            std::fs::write(&exe,"").unwrap();
+            std::fs::set_permissions(&exe,std::fs::Permissions::from_mode(0o755)).unwrap();
+            std::fs::write(dir.join("readme.txt"),"").unwrap();
+            std::os::unix::fs::symlink(dir.join("missing"),dir.join("dangling")).unwrap();
+
+            assert_eq!(get_install_dir_exes(dir.to_string_lossy().to_string()),vec![exe]);
+        }
+    }
 }
\ No newline at end of file
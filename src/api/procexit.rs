@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+/// Blocks on a process exiting, using the cheapest mechanism the platform offers
+pub enum ExitWaiter {
+    /// Linux 5.3+: the pidfd becomes readable when the process exits
+    #[cfg(target_os="linux")]
+    PidFd(std::os::fd::OwnedFd),
+    /// A process handle with SYNCHRONIZE access is signalled on exit
+    #[cfg(target_os="windows")]
+    Handle(windows_sys::Win32::Foundation::HANDLE),
+    /// Fallback that checks whether the pid is still alive
+    Poll(u32),
+    /// The process was already gone when the waiter was created
+    Exited
+}
+
+impl ExitWaiter {
+    #[cfg(target_os="linux")]
+    pub fn new(pid: u32) -> Self {
+        use std::os::fd::FromRawFd;
+
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open,pid as libc::pid_t,0) };
+
+        if fd >= 0 {
+            return ExitWaiter::PidFd(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd as i32) })
+        }
+
+        match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::ESRCH) => ExitWaiter::Exited,
+            _ => {
+                log::debug!("pidfd_open unavailable for {}, falling back to polling",pid);
+                Self::poll(pid)
+            }
+        }
+    }
+
+    #[cfg(target_os="windows")]
+    pub fn new(pid: u32) -> Self {
+        use windows_sys::Win32::System::Threading::{OpenProcess,PROCESS_SYNCHRONIZE};
+
+        let handle = unsafe { OpenProcess(PROCESS_SYNCHRONIZE,0,pid) };
+
+        if handle != 0 {
+            return ExitWaiter::Handle(handle)
+        }
+
+        log::debug!("Unable to open process {}, falling back to polling",pid);
+        Self::poll(pid)
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    pub fn new(pid: u32) -> Self {
+        Self::poll(pid)
+    }
+
+    fn poll(pid: u32) -> Self {
+        if is_alive(pid) {
+            ExitWaiter::Poll(pid)
+        } else {
+            ExitWaiter::Exited
+        }
+    }
+
+    pub fn method(&self) -> &'static str {
+        match self {
+            #[cfg(target_os="linux")]
+            ExitWaiter::PidFd(_) => "pidfd",
+            #[cfg(target_os="windows")]
+            ExitWaiter::Handle(_) => "handle",
+            ExitWaiter::Poll(_) => "poll",
+            ExitWaiter::Exited => "none"
+        }
+    }
+
+    /// Waits up to `timeout` for the process to exit, returning whether it has
+    pub fn wait(&self,timeout: Duration) -> bool {
+        match self {
+            #[cfg(target_os="linux")]
+            ExitWaiter::PidFd(fd) => {
+                use std::os::fd::AsRawFd;
+
+                let mut pollfd = libc::pollfd {
+                    fd: fd.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0
+                };
+
+                let ready = unsafe { libc::poll(&mut pollfd,1,timeout.as_millis() as libc::c_int) };
+                ready > 0 || (ready < 0 && std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted)
+            },
+            #[cfg(target_os="windows")]
+            ExitWaiter::Handle(handle) => {
+                use windows_sys::Win32::System::Threading::WaitForSingleObject;
+                use windows_sys::Win32::Foundation::WAIT_TIMEOUT;
+
+                unsafe { WaitForSingleObject(*handle,timeout.as_millis() as u32) != WAIT_TIMEOUT }
+            },
+            ExitWaiter::Poll(pid) => {
+                if !is_alive(*pid) {
+                    return true
+                }
+
+                std::thread::sleep(timeout);
+                !is_alive(*pid)
+            },
+            ExitWaiter::Exited => true
+        }
+    }
+}
+
+#[cfg(target_os="windows")]
+impl Drop for ExitWaiter {
+    fn drop(&mut self) {
+        if let ExitWaiter::Handle(handle) = self {
+            unsafe { windows_sys::Win32::Foundation::CloseHandle(*handle) };
+        }
+    }
+}
+
+fn is_alive(pid: u32) -> bool {
+    use process_alive::{state,State,Pid};
+
+    state(Pid::from(pid)) == State::Alive
+}
+
+#[cfg(all(test,target_os="linux"))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// A child that exits after `seconds`, reaped on another thread so it doesn't linger as a zombie
+    fn sleeper(seconds: &str) -> u32 {
+        let mut child = Command::new("sleep").arg(seconds).spawn().unwrap();
+        let pid = child.id();
+        std::thread::spawn(move || child.wait());
+        pid
+    }
+
+    fn waits_for_exit(waiter: &ExitWaiter) -> bool {
+        (0..40).any(|_| waiter.wait(Duration::from_millis(250)))
+    }
+
+    #[test]
+    fn pidfd_reports_exit() {
+        let waiter = ExitWaiter::new(sleeper("0.3"));
+
+        assert_eq!(waiter.method(),"pidfd");
+        assert!(!waiter.wait(Duration::from_millis(10)));
+        assert!(waits_for_exit(&waiter));
+    }
+
+    #[test]
+    fn poll_reports_exit() {
+        let waiter = ExitWaiter::poll(sleeper("0.3"));
+
+        assert_eq!(waiter.method(),"poll");
+        assert!(!waiter.wait(Duration::from_millis(10)));
+        assert!(waits_for_exit(&waiter));
+    }
+
+    #[test]
+    fn reports_already_exited_process() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let waiter = ExitWaiter::new(pid);
+
+        assert_eq!(waiter.method(),"none");
+        assert!(waiter.wait(Duration::ZERO));
+    }
+}
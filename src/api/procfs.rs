@@ -0,0 +1,747 @@
+use std::path::{Path,PathBuf};
+
+/// Length at which the kernel truncates "/proc/<pid>/comm"
+const COMM_LEN: usize = 15;
+
+/// A process as read from "/proc/<pid>"
+#[derive(Debug,Clone,PartialEq)]
+pub struct ProcRecord {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Kernel process name, truncated to 15 characters
+    pub comm: String,
+    pub state: char,
+    /// Target of "/proc/<pid>/exe", unavailable for kernel threads and other users' processes
+    pub exe: Option<PathBuf>,
+    pub cmdline: Vec<String>,
+    /// Target of "/proc/<pid>/cwd"
+    pub cwd: Option<PathBuf>,
+    /// Contents of "/proc/<pid>/environ", empty if it isn't readable
+    pub environ: Vec<(String,String)>,
+    /// Clock ticks after boot at which the process started
+    pub start_time: u64
+}
+
+/// The Windows executable a Wine/Proton process is running
+#[derive(Debug,Clone,PartialEq)]
+pub struct WineExe {
+    /// e.g. "Game.exe"
+    pub name: String,
+    /// The path as Wine sees it, e.g. "Z:\home\user\Game\Game.exe"
+    pub windows_path: String,
+    /// The same file on the Linux side, if the drive could be resolved
+    pub linux_path: Option<PathBuf>
+}
+
+impl ProcRecord {
+    /// The full command line as `ps -o cmd` would print it
+    pub fn command(&self) -> String {
+        if !self.cmdline.is_empty() {
+            return self.cmdline.join(" ")
+        }
+
+        match &self.exe {
+            Some(exe) => exe.to_string_lossy().to_string(),
+            None => format!("[{}]",self.comm)
+        }
+    }
+
+    pub fn env(&self,key: &str) -> Option<&str> {
+        self.environ
+            .iter()
+            .find(|(k,_)| k == key)
+            .map(|(_,v)| v.as_str())
+    }
+
+    /// Steam app id from the environment Steam launched the process with, inherited by launchers and children
+    pub fn app_id(&self) -> Option<u32> {
+        let app_id = ["SteamAppId","STEAM_COMPAT_APP_ID"]
+            .iter()
+            .find_map(|key| self.env(key)?.parse::<u32>().ok())
+            .filter(|app_id| *app_id != 0);
+
+        // SteamGameId is a 64-bit game id with the app id in its low 24 bits
+        app_id.or_else(|| {
+            self.env("SteamGameId")?
+                .parse::<u64>()
+                .ok()
+                .map(|game_id| (game_id & 0xFFFFFF) as u32)
+                .filter(|app_id| *app_id != 0)
+        })
+    }
+
+    pub fn exe_name(&self) -> Option<String> {
+        self.exe
+            .as_ref()
+            .and_then(|exe| exe.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+    }
+
+    /// Whether argv[0] is a Windows path such as "C:\Games\Game.bin", as Wine sets it for the programs it starts
+    fn windows_argv0(&self) -> bool {
+        self.cmdline.first().is_some_and(|arg| drive_path(arg).is_some())
+    }
+
+    /// Whether this looks like a process running under Wine or Proton. Inside a Proton prefix
+    /// (`STEAM_COMPAT_DATA_PATH` is set) a Windows path in argv[0] is enough, whatever its extension.
+    pub fn is_wine(&self) -> bool {
+        let is_exe = |name: &str| name.to_lowercase().ends_with(".exe");
+
+        self.exe_name().is_some_and(|name| name.starts_with("wine"))
+            || is_exe(&self.comm)
+            || self.cmdline.first().is_some_and(|arg| is_exe(arg))
+            || (self.windows_argv0() && self.env("STEAM_COMPAT_DATA_PATH").is_some())
+    }
+
+    /// Resolves the Windows executable for Wine/Proton processes. The preloader's argv holds the ".exe" path,
+    /// either as a Windows path ("Z:\...\Game.exe"), a Linux path or relative to the working dir.
+    pub fn wine_exe(&self) -> Option<WineExe> {
+        if !self.is_wine() {
+            return None
+        }
+
+        let arg = self.cmdline
+            .iter()
+            .find(|arg| arg.to_lowercase().ends_with(".exe"))
+            .or_else(|| self.cmdline.first().filter(|_| self.windows_argv0()))?;
+
+        let windows_path = arg.replace('/',"\\");
+        let name = windows_path.rsplit('\\').next().unwrap_or(&windows_path).to_string();
+
+        let linux_path = if arg.starts_with('/') {
+            Some(PathBuf::from(arg))
+        } else if let Some((drive,rest)) = drive_path(arg) {
+            self.dosdevice(drive).map(|root| root.join(rest))
+        } else {
+            self.cwd.as_ref().map(|cwd| cwd.join(arg.replace('\\',"/")))
+        };
+
+        Some(WineExe {
+            name,
+            windows_path,
+            linux_path
+        })
+    }
+
+    /// Linux directory a Wine drive letter maps to, via the prefix's "dosdevices" symlinks
+    fn dosdevice(&self,drive: char) -> Option<PathBuf> {
+        let prefix = self.env("WINEPREFIX")
+            .map(PathBuf::from)
+            .or_else(|| self.env("STEAM_COMPAT_DATA_PATH").map(|path| Path::new(path).join("pfx")));
+
+        let device = prefix.map(|prefix| prefix.join("dosdevices").join(format!("{}:",drive)));
+
+        match device {
+            Some(device) if device.exists() => Some(std::fs::canonicalize(&device).unwrap_or(device)),
+            // Wine maps Z: to the filesystem root by default
+            _ if drive == 'z' => Some(PathBuf::from("/")),
+            _ => None
+        }
+    }
+
+    /// Names this process could be known by: comm, the exe's file name and, under Wine, the Windows exe name
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![self.comm.clone()];
+        names.extend(self.exe_name());
+        names.extend(self.wine_exe().map(|wine| wine.name));
+        names
+    }
+
+    /// Case-insensitive name match. The kernel's 15 character comm truncation is only relied on
+    /// when the exe link can't be read, as otherwise the full name is known.
+    pub fn matches_name(&self,name: &str) -> bool {
+        let truncated = self.exe.is_none()
+            && name.len() > COMM_LEN
+            && self.comm.len() == COMM_LEN
+            && name.get(..COMM_LEN).is_some_and(|prefix| prefix.eq_ignore_ascii_case(&self.comm));
+
+        truncated || self.names().iter().any(|candidate| candidate.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Splits "C:\dir\file.exe" into the lowercase drive letter and the remaining relative path
+fn drive_path(path: &str) -> Option<(char,String)> {
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+
+    if chars.next() != Some(':') {
+        return None
+    }
+
+    let rest = chars
+        .as_str()
+        .split(['\\','/'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some((drive.to_ascii_lowercase(),rest))
+}
+
+fn parse_environ(bytes: &[u8]) -> Vec<(String,String)> {
+    bytes
+        .split(|b| *b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key,value) = entry.split_once('=')?;
+            Some((key.to_string(),value.to_string()))
+        })
+        .collect()
+}
+
+/// Resource counters from "/proc/<pid>/stat" and "/proc/<pid>/status"
+#[derive(Debug,Clone,PartialEq)]
+pub struct ProcUsage {
+    pub state: char,
+    /// User plus system CPU time, in clock ticks
+    pub cpu_ticks: u64,
+    pub threads: u32,
+    /// Clock ticks after boot at which the process started
+    pub start_time: u64,
+    /// Resident set size in bytes
+    pub rss: u64
+}
+
+/// Value of a "Key:   123 kB" line in a status file, in bytes
+fn status_bytes(status: &str,key: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.split(':').next() == Some(key))?;
+    let mut parts = line.split(':').nth(1)?.split_whitespace();
+    let value = parts.next()?.parse::<u64>().ok()?;
+
+    match parts.next() {
+        Some("kB") => Some(value * 1024),
+        _ => Some(value)
+    }
+}
+
+/// Fields of "/proc/<pid>/stat" that follow the parenthesised comm
+struct Stat {
+    comm: String,
+    state: char,
+    ppid: u32,
+    utime: u64,
+    stime: u64,
+    threads: u32,
+    start_time: u64,
+    rss_pages: u64
+}
+
+fn parse_stat(stat: &str) -> Option<Stat> {
+    // comm can itself contain spaces and parentheses, so split around the outermost pair
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let comm = stat.get(open + 1..close)?.to_string();
+    let fields = stat.get(close + 1..)?.split_whitespace().collect::<Vec<_>>();
+
+    Some(Stat {
+        comm,
+        state: fields.first()?.chars().next()?,
+        ppid: fields.get(1)?.parse().ok()?,
+        utime: fields.get(11)?.parse().ok()?,
+        stime: fields.get(12)?.parse().ok()?,
+        threads: fields.get(17)?.parse().ok()?,
+        start_time: fields.get(19)?.parse().ok()?,
+        rss_pages: fields.get(21).and_then(|rss| rss.parse().ok()).unwrap_or(0)
+    })
+}
+
+fn parse_cmdline(bytes: &[u8]) -> Vec<String> {
+    let mut args = bytes
+        .split(|b| *b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect::<Vec<_>>();
+
+    // The buffer ends with a NUL, which leaves an empty trailing entry
+    while args.last().is_some_and(|arg| arg.is_empty()) {
+        args.pop();
+    }
+
+    args
+}
+
+/// Clock ticks per second, the unit of CPU and start times in "stat"
+pub fn clock_ticks() -> u64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as u64,
+        _ => 100
+    }
+}
+
+fn page_size() -> u64 {
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as u64,
+        _ => 4096
+    }
+}
+
+/// Reader for a proc filesystem, rooted at "/proc" normally or at a fixture directory in tests
+pub struct ProcFs {
+    root: PathBuf
+}
+
+impl ProcFs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn system() -> Self {
+        Self::new("/proc")
+    }
+
+    pub fn pids(&self) -> Vec<u32> {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            log::error!("Unable to read \"{}\"",self.root.display());
+            return Vec::new()
+        };
+
+        let mut pids = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+
+        pids.sort_unstable();
+        pids
+    }
+
+    /// Reads a single process. Returns `None` if it has exited or its "stat" file is unreadable.
+    pub fn read(&self,pid: u32) -> Option<ProcRecord> {
+        let mut record = self.read_without_environ(pid)?;
+        record.environ = self.environ(pid);
+        Some(record)
+    }
+
+    fn environ(&self,pid: u32) -> Vec<(String,String)> {
+        std::fs::read(self.root.join(pid.to_string()).join("environ"))
+            .map(|bytes| parse_environ(&bytes))
+            .unwrap_or_default()
+    }
+
+    /// Like `read`, but leaves `environ` empty for scans that only need it for a few processes
+    fn read_without_environ(&self,pid: u32) -> Option<ProcRecord> {
+        let dir = self.root.join(pid.to_string());
+        let stat = parse_stat(&std::fs::read_to_string(dir.join("stat")).ok()?)?;
+
+        let comm = std::fs::read_to_string(dir.join("comm"))
+            .map(|comm| comm.trim_end_matches('\n').to_string())
+            .unwrap_or(stat.comm);
+
+        Some(ProcRecord {
+            pid,
+            ppid: stat.ppid,
+            comm,
+            state: stat.state,
+            exe: std::fs::read_link(dir.join("exe")).ok(),
+            cmdline: std::fs::read(dir.join("cmdline"))
+                .map(|bytes| parse_cmdline(&bytes))
+                .unwrap_or_default(),
+            cwd: std::fs::read_link(dir.join("cwd")).ok(),
+            environ: Vec::new(),
+            start_time: stat.start_time
+        })
+    }
+
+    /// CPU time, thread count and memory of a process, or `None` if it has exited
+    pub fn usage(&self,pid: u32) -> Option<ProcUsage> {
+        let dir = self.root.join(pid.to_string());
+        let stat = parse_stat(&std::fs::read_to_string(dir.join("stat")).ok()?)?;
+
+        // "status" reports VmRSS directly in kB, whereas "stat" counts pages
+        let rss = std::fs::read_to_string(dir.join("status"))
+            .ok()
+            .and_then(|status| status_bytes(&status,"VmRSS"))
+            .unwrap_or_else(|| stat.rss_pages * page_size());
+
+        Some(ProcUsage {
+            state: stat.state,
+            cpu_ticks: stat.utime + stat.stime,
+            threads: stat.threads,
+            start_time: stat.start_time,
+            rss
+        })
+    }
+
+    /// Seconds since the Unix epoch at which the system booted, from the "btime" line of "/proc/stat"
+    pub fn boot_time(&self) -> Option<u64> {
+        std::fs::read_to_string(self.root.join("stat"))
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("btime")?.trim().parse().ok())
+    }
+
+    pub fn processes(&self) -> Vec<ProcRecord> {
+        self.pids()
+            .into_iter()
+            .filter_map(|pid| self.read(pid))
+            .collect()
+    }
+
+    /// Processes matching one of `names` (see `ProcRecord::matches_name`), paired with the name they matched
+    pub fn find_by_name<'a>(&self,names: &'a [String]) -> Vec<(&'a str,ProcRecord)> {
+        let mut processes = self.pids()
+            .into_iter()
+            .filter_map(|pid| self.read_without_environ(pid))
+            .collect::<Vec<_>>();
+
+        // The environment only matters for Proton processes, so it's read just for those and for matches
+        processes.retain(|p| p.windows_argv0() || names.iter().any(|name| p.matches_name(name)));
+
+        for process in processes.iter_mut() {
+            process.environ = self.environ(process.pid);
+        }
+
+        let mut matches: Vec<(&str,ProcRecord)> = Vec::new();
+
+        for name in names {
+            for process in processes.iter().filter(|p| p.matches_name(name)) {
+                if !matches.iter().any(|(_,matched)| matched.pid == process.pid) {
+                    matches.push((name.as_str(),process.clone()));
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Every process started for `app_id`, identified by its environment, plus any children that cleared it
+    pub fn find_by_app_id(&self,app_id: u32) -> Vec<ProcRecord> {
+        let processes = self.processes();
+
+        let roots = processes
+            .iter()
+            .filter(|p| p.app_id() == Some(app_id))
+            .map(|p| p.pid)
+            .collect::<Vec<_>>();
+
+        with_descendants(&processes,&roots)
+    }
+}
+
+/// Returns `roots` plus all of their descendants, in pid order
+pub fn with_descendants(processes: &[ProcRecord],roots: &[u32]) -> Vec<ProcRecord> {
+    let mut pids = roots.to_vec();
+    let mut index = 0;
+
+    while index < pids.len() {
+        let parent = pids[index];
+
+        for child in processes.iter().filter(|p| p.ppid == parent) {
+            if !pids.contains(&child.pid) {
+                pids.push(child.pid);
+            }
+        }
+
+        index += 1;
+    }
+
+    processes
+        .iter()
+        .filter(|p| pids.contains(&p.pid))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::path::{Path,PathBuf};
+    use crate::api::testdir::TempDir;
+
+    /// Builds a fake "/proc" tree in a temp dir, removed again on drop
+    pub struct FakeProc {
+        root: TempDir
+    }
+
+    impl FakeProc {
+        pub fn new(name: &str) -> Self {
+            Self { root: TempDir::new(&format!("proc-{}",name)) }
+        }
+
+        pub fn dir(&self,pid: u32) -> PathBuf {
+            let dir = self.root.join(pid.to_string());
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        }
+
+        pub fn write(&self,pid: u32,file: &str,contents: impl AsRef<[u8]>) -> &Self {
+            std::fs::write(self.dir(pid).join(file),contents).unwrap();
+            self
+        }
+
+        /// Adds a process with the given comm, parent, exe target and argv
+        pub fn process(&self,pid: u32,ppid: u32,comm: &str,exe: Option<&str>,argv: &[&str]) -> &Self {
+            self.write(pid,"stat",format!(
+                "{} ({}) S {} {} {} 0 -1 4194560 100 0 0 0 12 3 0 0 20 0 1 0 {} 1000000 200 18446744073709551615\n",
+                pid,comm,ppid,pid,pid,1000 + pid
+            ));
+            self.write(pid,"comm",format!("{}\n",comm));
+
+            let mut cmdline = argv.join("\0");
+            if !argv.is_empty() {
+                cmdline.push('\0');
+            }
+            self.write(pid,"cmdline",cmdline);
+
+            if let Some(exe) = exe {
+                std::os::unix::fs::symlink(exe,self.dir(pid).join("exe")).unwrap();
+            }
+
+            self
+        }
+
+        pub fn environ(&self,pid: u32,vars: &[(&str,&str)]) -> &Self {
+            let environ = vars
+                .iter()
+                .map(|(key,value)| format!("{}={}\0",key,value))
+                .collect::<String>();
+
+            self.write(pid,"environ",environ)
+        }
+
+        pub fn cwd(&self,pid: u32,cwd: &str) -> &Self {
+            std::os::unix::fs::symlink(cwd,self.dir(pid).join("cwd")).unwrap();
+            self
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.root
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fixture::FakeProc;
+
+    #[test]
+    fn parses_stat_with_awkward_comm() {
+        let stat = parse_stat("42 (Game (x64) ) R 7 42 42 0 -1 0 0 0 0 0 5 1 0 0 20 0 3 0 98765 0 0").unwrap();
+
+        assert_eq!(stat.comm,"Game (x64) ");
+        assert_eq!(stat.state,'R');
+        assert_eq!(stat.ppid,7);
+        assert_eq!(stat.start_time,98765);
+        assert_eq!((stat.utime,stat.stime,stat.threads),(5,1,3));
+        assert!(parse_stat("42 (truncated) R 7").is_none());
+    }
+
+    #[test]
+    fn splits_cmdline_on_nul() {
+        assert_eq!(parse_cmdline(b"/usr/bin/game\0--flag\0with space\0"),vec!["/usr/bin/game","--flag","with space"]);
+        assert_eq!(parse_cmdline(b"a\0\0b\0"),vec!["a","","b"]);
+        assert!(parse_cmdline(b"").is_empty());
+    }
+
+    #[test]
+    fn reads_processes() {
+        let proc = FakeProc::new("reads");
+        proc.process(1,0,"systemd",Some("/usr/lib/systemd/systemd"),&["/sbin/init","splash"])
+            .process(200,1,"kworker/0:1",None,&[])
+            .process(300,1,"MyGame.x86_64",Some("/games/MyGame/MyGame.x86_64"),&["/games/MyGame/MyGame.x86_64","-windowed"]);
+
+        std::fs::create_dir_all(proc.path().join("self")).unwrap();
+        std::fs::create_dir_all(proc.path().join("sys")).unwrap();
+
+        let procfs = ProcFs::new(proc.path());
+        assert_eq!(procfs.pids(),vec![1,200,300]);
+
+        let game = procfs.read(300).unwrap();
+        assert_eq!(game.ppid,1);
+        assert_eq!(game.comm,"MyGame.x86_64");
+        assert_eq!(game.exe,Some(PathBuf::from("/games/MyGame/MyGame.x86_64")));
+        assert_eq!(game.command(),"/games/MyGame/MyGame.x86_64 -windowed");
+        assert_eq!(game.start_time,1300);
+
+        let kthread = procfs.read(200).unwrap();
+        assert_eq!(kthread.exe,None);
+        assert_eq!(kthread.command(),"[kworker/0:1]");
+    }
+
+    #[test]
+    fn skips_vanished_and_partial_processes() {
+        let proc = FakeProc::new("vanished");
+        proc.process(10,1,"alive",None,&["alive"]);
+        proc.write(11,"comm","exited\n");
+        proc.write(12,"stat","garbage");
+
+        let procfs = ProcFs::new(proc.path());
+        assert_eq!(procfs.processes().iter().map(|p| p.pid).collect::<Vec<_>>(),vec![10]);
+        assert!(procfs.read(99).is_none());
+    }
+
+    #[test]
+    fn falls_back_to_stat_comm() {
+        let proc = FakeProc::new("statcomm");
+        proc.process(5,1,"fromstat",None,&[]);
+        std::fs::remove_file(proc.dir(5).join("comm")).unwrap();
+
+        assert_eq!(ProcFs::new(proc.path()).read(5).unwrap().comm,"fromstat");
+    }
+
+    #[test]
+    fn finds_processes_by_name() {
+        let proc = FakeProc::new("byname");
+        proc.process(20,1,"game",Some("/games/game"),&["/games/game"])
+            .process(21,20,"Game",Some("/games/Game"),&["/games/Game","--child"])
+            .process(22,1,"other",None,&["other"]);
+
+        let names = vec!["GAME".to_string(),"missing".to_string()];
+        let matches = ProcFs::new(proc.path()).find_by_name(&names);
+
+        assert_eq!(matches.iter().map(|(name,p)| (*name,p.pid)).collect::<Vec<_>>(),vec![("GAME",20),("GAME",21)]);
+    }
+
+    #[test]
+    fn matches_truncated_comm_and_exe_name() {
+        let proc = FakeProc::new("truncated");
+        proc.process(30,1,"VeryLongGameNam",Some("/games/VeryLongGameName.x86_64"),&["./VeryLongGameName.x86_64"]);
+
+        let procfs = ProcFs::new(proc.path());
+        let game = procfs.read(30).unwrap();
+
+        assert!(game.matches_name("VeryLongGameName.x86_64"));
+        assert!(game.matches_name("verylonggamename.X86_64"));
+        assert!(!game.matches_name("VeryLongGameNam_Other"));
+        assert!(!game.is_wine());
+        assert!(game.wine_exe().is_none());
+
+        // Without a readable exe link only the truncated comm is available
+        proc.process(31,1,"OtherUsersGame_",None,&[]);
+        let other = procfs.read(31).unwrap();
+        assert!(other.matches_name("OtherUsersGame_Binary"));
+        assert!(!other.matches_name("OtherUsersGame"));
+    }
+
+    #[test]
+    fn splits_drive_paths() {
+        assert_eq!(drive_path(r"Z:\home\user\Game.exe"),Some(('z',"home/user/Game.exe".to_string())));
+        assert_eq!(drive_path(r"c:/Program Files/Game.exe"),Some(('c',"Program Files/Game.exe".to_string())));
+        assert_eq!(drive_path("Game.exe"),None);
+        assert_eq!(drive_path("/usr/bin/wine"),None);
+    }
+
+    #[test]
+    fn resolves_proton_processes() {
+        let proc = FakeProc::new("proton");
+        let compat = proc.path().join("compatdata/123");
+        let drive_c = compat.join("pfx/drive_c");
+        std::fs::create_dir_all(compat.join("pfx/dosdevices")).unwrap();
+        std::fs::create_dir_all(&drive_c).unwrap();
+        std::os::unix::fs::symlink(&drive_c,compat.join("pfx/dosdevices/c:")).unwrap();
+        let compat = compat.to_string_lossy().to_string();
+
+        proc.process(40,1,"SomeVeryLongGam",Some("/steam/Proton/files/bin/wine64-preloader"),&[r"Z:\games\Some Game\SomeVeryLongGame.exe","-dx12"])
+            .environ(40,&[("STEAM_COMPAT_DATA_PATH",&compat),("SteamAppId","123")])
+            .process(41,40,"Launcher.exe",Some("/steam/Proton/files/bin/wine64-preloader"),&[r"C:\Program Files\Pub\Launcher.exe"])
+            .environ(41,&[("STEAM_COMPAT_DATA_PATH",&compat)])
+            .process(42,1,"wine64-preloade",Some("/usr/bin/wine64-preloader"),&["/usr/bin/wine64-preloader","Tool.exe"])
+            .cwd(42,"/games/tool")
+            .process(43,1,"wineserver",Some("/usr/bin/wineserver"),&["/usr/bin/wineserver"]);
+
+        let procfs = ProcFs::new(proc.path());
+
+        let game = procfs.read(40).unwrap().wine_exe().unwrap();
+        assert_eq!(game.name,"SomeVeryLongGame.exe");
+        assert_eq!(game.windows_path,r"Z:\games\Some Game\SomeVeryLongGame.exe");
+        assert_eq!(game.linux_path,Some(PathBuf::from("/games/Some Game/SomeVeryLongGame.exe")));
+
+        let launcher = procfs.read(41).unwrap().wine_exe().unwrap();
+        assert_eq!(launcher.name,"Launcher.exe");
+        assert_eq!(launcher.linux_path,Some(std::fs::canonicalize(&drive_c).unwrap().join("Program Files/Pub/Launcher.exe")));
+
+        let tool = procfs.read(42).unwrap().wine_exe().unwrap();
+        assert_eq!(tool.linux_path,Some(PathBuf::from("/games/tool/Tool.exe")));
+
+        assert!(procfs.read(43).unwrap().is_wine());
+        assert!(procfs.read(43).unwrap().wine_exe().is_none());
+
+        let names = vec!["someverylonggame.exe".to_string(),"Launcher.exe".to_string()];
+        let matches = procfs.find_by_name(&names);
+        assert_eq!(matches.iter().map(|(name,p)| (*name,p.pid)).collect::<Vec<_>>(),vec![("someverylonggame.exe",40),("Launcher.exe",41)]);
+    }
+
+    #[test]
+    fn uses_proton_prefix_and_reads_environ_of_matches() {
+        let proc = FakeProc::new("prefix");
+        let compat = proc.path().join("compatdata/123").to_string_lossy().to_string();
+
+        proc.process(60,1,"Game.bin",None,&[r"Z:\games\Game.bin"])
+            .environ(60,&[("STEAM_COMPAT_DATA_PATH",&compat),("SteamAppId","123")])
+            .process(61,1,"Other.bin",None,&[r"Z:\games\Other.bin"])
+            .process(62,1,"python3",Some("/usr/bin/python3"),&["python3","/steam/proton","waitforexitandrun",r"Z:\games\Game.exe"])
+            .environ(62,&[("STEAM_COMPAT_DATA_PATH",&compat)]);
+
+        let procfs = ProcFs::new(proc.path());
+
+        let game = procfs.read(60).unwrap();
+        assert!(game.is_wine());
+        assert_eq!(game.wine_exe().map(|wine| wine.linux_path),Some(Some(PathBuf::from("/games/Game.bin"))));
+        assert!(!procfs.read(61).unwrap().is_wine());
+        assert!(!procfs.read(62).unwrap().is_wine());
+
+        let names = vec!["game.bin".to_string(),"Game.exe".to_string()];
+        let matches = procfs.find_by_name(&names);
+        assert_eq!(matches.iter().map(|(name,p)| (*name,p.pid)).collect::<Vec<_>>(),vec![("game.bin",60)]);
+        assert_eq!(matches[0].1.app_id(),Some(123));
+    }
+
+    #[test]
+    fn parses_environ() {
+        assert_eq!(parse_environ(b"A=1\0B=x=y\0broken\0"),vec![("A".to_string(),"1".to_string()),("B".to_string(),"x=y".to_string())]);
+    }
+
+    #[test]
+    fn reads_app_id_from_environment() {
+        let proc = FakeProc::new("appid");
+        proc.process(50,1,"reaper",Some("/steam/ubuntu12_32/reaper"),&["reaper","SteamLaunch","AppId=480"])
+            .environ(50,&[("SteamAppId","480"),("SteamGameId","480")])
+            .process(51,50,"launcher",Some("/games/launcher"),&["/games/launcher"])
+            .environ(51,&[("SteamAppId","480")])
+            .process(52,51,"game",Some("/games/game"),&["/games/game"])
+            .environ(52,&[("PATH","/usr/bin")])
+            .process(53,52,"crashhandler",Some("/games/crashhandler"),&["/games/crashhandler"])
+            .process(54,1,"proton",None,&["proton"])
+            .environ(54,&[("STEAM_COMPAT_APP_ID","70")])
+            .process(55,1,"shortcut",None,&["shortcut"])
+            .environ(55,&[("SteamAppId","0"),("SteamGameId","12345678901234567890")])
+            .process(56,1,"unrelated",None,&["unrelated"])
+            .environ(56,&[("SteamAppId","4800")]);
+
+        let procfs = ProcFs::new(proc.path());
+
+        assert_eq!(procfs.read(50).unwrap().app_id(),Some(480));
+        assert_eq!(procfs.read(52).unwrap().app_id(),None);
+        assert_eq!(procfs.read(54).unwrap().app_id(),Some(70));
+        assert_eq!(procfs.read(55).unwrap().app_id(),Some((12345678901234567890u64 & 0xFFFFFF) as u32));
+
+        let pids = procfs.find_by_app_id(480).iter().map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(pids,vec![50,51,52,53]);
+        assert!(procfs.find_by_app_id(1).is_empty());
+    }
+
+    #[test]
+    fn missing_root_is_empty() {
+        let procfs = ProcFs::new("/nonexistent/steamworksjs/proc");
+        assert!(procfs.processes().is_empty());
+    }
+
+    #[test]
+    fn reads_usage() {
+        let proc = FakeProc::new("usage");
+        proc.process(300,1,"MyGame.x86_64",None,&[])
+            .write(300,"status","Name:\tMyGame.x86_64\nVmRSS:\t  204800 kB\nThreads:\t1\n");
+        proc.process(301,1,"nostatus",None,&[]);
+        std::fs::write(proc.path().join("stat"),"cpu  1 2 3 4\nbtime 1700000000\nprocesses 9\n").unwrap();
+
+        let procfs = ProcFs::new(proc.path());
+        let usage = procfs.usage(300).unwrap();
+        assert_eq!(usage.state,'S');
+        assert_eq!(usage.cpu_ticks,15);
+        assert_eq!(usage.threads,1);
+        assert_eq!(usage.start_time,1300);
+        assert_eq!(usage.rss,204800 * 1024);
+
+        assert_eq!(procfs.usage(301).unwrap().rss,200 * page_size());
+        assert_eq!(procfs.usage(999),None);
+        assert_eq!(procfs.boot_time(),Some(1700000000));
+    }
+}
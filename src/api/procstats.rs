@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration,Instant};
+
+/// A process' resource usage at one point in time
+#[derive(Debug,Clone,PartialEq)]
+pub struct Sample {
+    /// User plus system CPU time, in milliseconds
+    pub cpu_time_ms: f64,
+    pub rss_bytes: u64,
+    pub threads: u32,
+    /// Milliseconds since the Unix epoch
+    pub start_time_ms: i64,
+    /// Scheduler state letter where the platform reports one, e.g. "R", "S", "D" or "Z" on Linux
+    pub state: Option<String>
+}
+
+/// Samples older than this are dropped, so exited processes don't accumulate
+const SAMPLE_TTL: Duration = Duration::from_secs(600);
+
+struct Previous {
+    taken: Instant,
+    cpu_time_ms: f64,
+    start_time_ms: i64
+}
+
+lazy_static! {
+    static ref PREVIOUS: Mutex<HashMap<u32,Previous>> = Mutex::new(HashMap::new());
+}
+
+#[cfg(target_os="linux")]
+pub fn sample(pid: u32) -> Option<Sample> {
+    use crate::api::procfs::{ProcFs,clock_ticks};
+
+    let procfs = ProcFs::system();
+    let usage = procfs.usage(pid)?;
+    let ticks = clock_ticks() as f64;
+    let boot_ms = procfs.boot_time().unwrap_or(0) as f64 * 1000.0;
+
+    Some(Sample {
+        cpu_time_ms: usage.cpu_ticks as f64 * 1000.0 / ticks,
+        rss_bytes: usage.rss,
+        threads: usage.threads,
+        start_time_ms: (boot_ms + usage.start_time as f64 * 1000.0 / ticks) as i64,
+        state: Some(usage.state.to_string())
+    })
+}
+
+#[cfg(target_os="windows")]
+pub fn sample(pid: u32) -> Option<Sample> {
+    /// Milliseconds between the FILETIME epoch (1601) and the Unix epoch
+    const UNIX_EPOCH_MS: i64 = 11_644_473_600_000;
+
+    let usage = crate::api::win32process::usage(pid)?;
+
+    Some(Sample {
+        cpu_time_ms: usage.cpu_time as f64 / 10_000.0,
+        rss_bytes: usage.working_set,
+        threads: usage.threads,
+        start_time_ms: (usage.start_time / 10_000) as i64 - UNIX_EPOCH_MS,
+        state: None
+    })
+}
+
+#[cfg(not(any(target_os="windows",target_os="linux")))]
+pub fn sample(_pid: u32) -> Option<Sample> {
+    log::error!("Process stats are not supported on this platform");
+    None
+}
+
+/// CPU usage since the previous call for `pid`, where 100 is one fully busy core. `None` on the first call,
+/// or if the pid now belongs to a different process.
+pub fn cpu_percent(pid: u32,sample: &Sample) -> Option<f64> {
+    cpu_percent_at(&mut PREVIOUS.lock().unwrap(),pid,sample,Instant::now())
+}
+
+fn cpu_percent_at(previous: &mut HashMap<u32,Previous>,pid: u32,sample: &Sample,now: Instant) -> Option<f64> {
+    previous.retain(|_,entry| now.duration_since(entry.taken) < SAMPLE_TTL);
+
+    let last = previous.insert(pid,Previous {
+        taken: now,
+        cpu_time_ms: sample.cpu_time_ms,
+        start_time_ms: sample.start_time_ms
+    })?;
+
+    let elapsed_ms = now.duration_since(last.taken).as_secs_f64() * 1000.0;
+
+    if last.start_time_ms != sample.start_time_ms || elapsed_ms <= 0.0 {
+        return None
+    }
+
+    Some(((sample.cpu_time_ms - last.cpu_time_ms).max(0.0) / elapsed_ms) * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(cpu_time_ms: f64,start_time_ms: i64) -> Sample {
+        Sample {
+            cpu_time_ms,
+            rss_bytes: 0,
+            threads: 1,
+            start_time_ms,
+            state: None
+        }
+    }
+
+    #[test]
+    fn measures_against_previous_sample() {
+        let mut previous = HashMap::new();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(1000.0,5),at(0)),None);
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(1250.0,5),at(500)),Some(50.0));
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(3250.0,5),at(1500)),Some(200.0));
+
+        // CPU time never goes backwards for the same process, but a bad reading shouldn't go negative
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(3000.0,5),at(2500)),Some(0.0));
+    }
+
+    #[test]
+    fn restarts_for_reused_pid() {
+        let mut previous = HashMap::new();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        cpu_percent_at(&mut previous,1,&sample(5000.0,5),at(0));
+
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(10.0,9),at(1000)),None);
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(110.0,9),at(2000)),Some(10.0));
+    }
+
+    #[test]
+    fn drops_stale_samples() {
+        let mut previous = HashMap::new();
+        let start = Instant::now();
+
+        cpu_percent_at(&mut previous,1,&sample(0.0,5),start);
+        cpu_percent_at(&mut previous,2,&sample(0.0,5),start + SAMPLE_TTL);
+
+        assert!(!previous.contains_key(&1));
+        assert_eq!(cpu_percent_at(&mut previous,1,&sample(100.0,5),start + SAMPLE_TTL),None);
+    }
+}
@@ -0,0 +1,286 @@
+/// A running process, as listed by the platform's process table
+#[derive(Debug,Clone,PartialEq)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Process name. For Wine/Proton processes this is the Windows exe name rather than the loader.
+    pub name: String,
+    /// Executable path, empty if it can't be read. For Wine/Proton processes this is the Windows exe's Linux path.
+    pub exe: String,
+    pub cmdline: String,
+    /// Steam app id the process was launched for, where the platform exposes it
+    pub app_id: Option<u32>,
+    /// Clock ticks after boot on Linux, creation time as a FILETIME on Windows, 0 if unknown.
+    /// Unlike the name it survives exec, and unlike the pid it isn't reused.
+    pub start_time: u64
+}
+
+impl ProcessEntry {
+    /// Same pid and start time. Where a start time is unknown the names are compared instead.
+    pub fn is_same_process(&self,other: &ProcessEntry) -> bool {
+        self.pid == other.pid && match (self.start_time,other.start_time) {
+            (0,_) | (_,0) => self.name == other.name,
+            (a,b) => a == b
+        }
+    }
+}
+
+#[cfg(target_os="linux")]
+pub fn snapshot() -> Vec<ProcessEntry> {
+    use crate::api::procfs::ProcFs;
+
+    ProcFs::system()
+        .processes()
+        .into_iter()
+        .map(|process| {
+            let wine = process.wine_exe();
+            let exe = wine.as_ref().and_then(|wine| wine.linux_path.clone()).or_else(|| process.exe.clone());
+
+            ProcessEntry {
+                pid: process.pid,
+                ppid: process.ppid,
+                name: wine.map(|wine| wine.name).unwrap_or_else(|| process.comm.clone()),
+                exe: exe.map(|exe| exe.to_string_lossy().to_string()).unwrap_or_default(),
+                cmdline: process.command(),
+                app_id: process.app_id(),
+                start_time: process.start_time
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os="windows")]
+pub fn snapshot() -> Vec<ProcessEntry> {
+    crate::api::win32process::processes()
+        .into_iter()
+        .map(|process| ProcessEntry {
+            pid: process.pid,
+            ppid: process.ppid,
+            name: process.name,
+            exe: process.exe.unwrap_or_default(),
+            cmdline: process.cmdline.unwrap_or_default(),
+            app_id: None,
+            start_time: process.start_time
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os="windows",target_os="linux")))]
+pub fn snapshot() -> Vec<ProcessEntry> {
+    log::error!("Process enumeration is not supported on this platform");
+    Vec::new()
+}
+
+/// What a tree is rooted at
+#[derive(Debug,Clone)]
+pub enum TreeRoot {
+    Pid(u32),
+    /// Processes launched for the app. `exes` is used to find them where `app_id` isn't available (Windows).
+    App { app_id: u32, exes: Vec<String> }
+}
+
+impl TreeRoot {
+    fn pids(&self,processes: &[ProcessEntry]) -> Vec<u32> {
+        match self {
+            TreeRoot::Pid(pid) => vec![*pid],
+            TreeRoot::App { app_id, exes } => processes
+                .iter()
+                .filter(|p| match p.app_id {
+                    Some(id) => id == *app_id,
+                    None => exes.iter().any(|exe| exe.eq_ignore_ascii_case(&p.name))
+                })
+                .map(|p| p.pid)
+                .collect()
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum TreeChange {
+    Join(ProcessEntry),
+    Leave(ProcessEntry)
+}
+
+/// Processes descending from a root. Members stay in the tree until they exit, so a game keeps being
+/// tracked after its launcher quits and it is reparented.
+#[derive(Debug,Default)]
+pub struct ProcessTree {
+    members: Vec<ProcessEntry>,
+    /// Set once a pid root has been seen, after which the pid is no longer trusted in case it is reused
+    seeded: bool
+}
+
+impl ProcessTree {
+    pub fn members(&self) -> &[ProcessEntry] {
+        &self.members
+    }
+
+    fn parent(&self,pid: u32) -> Option<u32> {
+        let ppid = self.members.iter().find(|p| p.pid == pid)?.ppid;
+        (ppid != pid && self.members.iter().any(|p| p.pid == ppid)).then_some(ppid)
+    }
+
+    /// Distance from the nearest ancestor whose parent isn't in the tree
+    pub fn depth(&self,pid: u32) -> u32 {
+        let mut depth = 0;
+        let mut current = pid;
+
+        while let Some(parent) = self.parent(current) {
+            // Guards against ppid cycles from a snapshot taken mid pid reuse
+            if depth as usize >= self.members.len() {
+                break
+            }
+
+            depth += 1;
+            current = parent;
+        }
+
+        depth
+    }
+
+    /// Recomputes membership from a fresh process list, returning who joined and left
+    pub fn update(&mut self,processes: &[ProcessEntry],root: &TreeRoot) -> Vec<TreeChange> {
+        // A pid that now belongs to a process with another start time was reused, so the member has exited
+        let alive = self.members
+            .iter()
+            .filter(|member| processes.iter().any(|p| p.is_same_process(member)))
+            .map(|member| member.pid);
+
+        let mut pids = match root {
+            TreeRoot::Pid(_) if self.seeded => Vec::new(),
+            _ => root.pids(processes)
+        };
+
+        pids.extend(alive);
+        pids.retain(|pid| processes.iter().any(|p| p.pid == *pid));
+        pids.sort_unstable();
+        pids.dedup();
+
+        let mut index = 0;
+
+        while index < pids.len() {
+            let parent = pids[index];
+
+            for child in processes.iter().filter(|p| p.ppid == parent && p.pid != parent) {
+                if !pids.contains(&child.pid) {
+                    pids.push(child.pid);
+                }
+            }
+
+            index += 1;
+        }
+
+        let members = processes
+            .iter()
+            .filter(|p| pids.contains(&p.pid))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut changes = self.members
+            .iter()
+            .filter(|old| !members.iter().any(|new| new.is_same_process(old)))
+            .cloned()
+            .map(TreeChange::Leave)
+            .collect::<Vec<_>>();
+
+        changes.extend(
+            members
+                .iter()
+                .filter(|new| !self.members.iter().any(|old| old.is_same_process(new)))
+                .cloned()
+                .map(TreeChange::Join)
+        );
+
+        self.seeded |= !members.is_empty();
+        self.members = members;
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32,ppid: u32,name: &str,start_time: u64) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            ppid,
+            name: name.to_string(),
+            exe: format!("/games/{}",name),
+            cmdline: name.to_string(),
+            app_id: None,
+            start_time
+        }
+    }
+
+    fn summary(changes: &[TreeChange]) -> Vec<(&'static str,u32)> {
+        changes
+            .iter()
+            .map(|change| match change {
+                TreeChange::Join(process) => ("join",process.pid),
+                TreeChange::Leave(process) => ("leave",process.pid)
+            })
+            .collect()
+    }
+
+    fn pids(tree: &ProcessTree) -> Vec<u32> {
+        tree.members().iter().map(|process| process.pid).collect()
+    }
+
+    #[test]
+    fn tracks_joins_and_leaves() {
+        let root = TreeRoot::Pid(10);
+        let mut tree = ProcessTree::default();
+
+        let changes = tree.update(&[process(1,0,"init",1),process(10,1,"launcher",100),process(11,10,"game",110)],&root);
+        assert_eq!(summary(&changes),vec![("join",10),("join",11)]);
+        assert_eq!(tree.depth(11),1);
+
+        // The launcher exits and the game is reparented to init, but stays in the tree
+        let changes = tree.update(&[process(1,0,"init",1),process(11,1,"game",110),process(12,11,"helper",120)],&root);
+        assert_eq!(summary(&changes),vec![("leave",10),("join",12)]);
+        assert_eq!(pids(&tree),vec![11,12]);
+        assert_eq!(tree.depth(11),0);
+
+        let changes = tree.update(&[process(1,0,"init",1)],&root);
+        assert_eq!(summary(&changes),vec![("leave",11),("leave",12)]);
+        assert!(tree.members().is_empty());
+    }
+
+    #[test]
+    fn keeps_root_across_exec() {
+        let root = TreeRoot::Pid(10);
+        let mut tree = ProcessTree::default();
+
+        tree.update(&[process(10,1,"game.sh",100),process(11,10,"helper",110)],&root);
+
+        // "exec ./game.x86_64" keeps the pid and start time but changes the name
+        let changes = tree.update(&[process(10,1,"game.x86_64",100),process(11,10,"helper",110)],&root);
+        assert!(changes.is_empty());
+        assert_eq!(pids(&tree),vec![10,11]);
+        assert_eq!(tree.members()[0].name,"game.x86_64");
+    }
+
+    #[test]
+    fn treats_reused_pid_as_exited() {
+        let root = TreeRoot::Pid(10);
+        let mut tree = ProcessTree::default();
+
+        tree.update(&[process(10,1,"game",100)],&root);
+
+        // Same pid and name, but a later start time: the game exited and the pid went to a new process
+        let changes = tree.update(&[process(10,1,"game",500)],&root);
+        assert_eq!(summary(&changes),vec![("leave",10)]);
+        assert!(tree.members().is_empty());
+
+        // A pid root isn't picked up again once it has been seen
+        assert!(tree.update(&[process(10,1,"game",500)],&root).is_empty());
+    }
+
+    #[test]
+    fn falls_back_to_name_without_start_time() {
+        assert!(process(10,1,"game",0).is_same_process(&process(10,1,"game",100)));
+        assert!(!process(10,1,"game",0).is_same_process(&process(10,1,"other",0)));
+        assert!(!process(10,1,"game",100).is_same_process(&process(11,1,"game",100)));
+    }
+}
@@ -0,0 +1,61 @@
+use napi_derive::napi;
+
+#[napi]
+pub mod runningapp {
+    use napi::JsFunction;
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{ThreadsafeFunction,ThreadsafeFunctionCallMode,ErrorStrategy};
+    use std::time::Duration;
+    use log::info;
+    use crate::api::watcher::WatchHandle;
+    use crate::api::steamdir::steamdir::registry_dword;
+
+    const DEFAULT_INTERVAL_MS: u32 = 1000;
+
+    #[napi(object)]
+    pub struct RunningAppChange {
+        /// 0 when no game is running
+        pub app_id: u32,
+        pub previous_app_id: u32
+    }
+
+    /// App id of the game Steam currently reports as running, read from Steam's registry ("registry.vdf" on Linux), or 0
+    #[napi]
+    pub fn get_running_app_id() -> u32 {
+        registry_dword("Software\\Valve\\Steam","RunningAppID").unwrap_or(0)
+    }
+
+    /// Calls `handler` whenever Steam's running app id changes, including once on start if a game is already running.
+    /// On Linux and macOS each poll only stats registry.vdf, which is parsed again only after Steam rewrites it.
+    #[napi]
+    pub fn watch_running_app(
+        #[napi(ts_arg_type = "(change: RunningAppChange) => void")] handler: JsFunction,
+        interval_ms: Option<u32>
+    ) -> Result<WatchHandle,Error> {
+        let threadsafe_handler: ThreadsafeFunction<RunningAppChange,ErrorStrategy::Fatal> = handler
+            .create_threadsafe_function(0,|ctx| Ok(vec![ctx.value]))?;
+
+        let mut previous = 0;
+
+        Ok(WatchHandle::spawn(
+            "runningapp-watcher",
+            Duration::from_millis(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS) as u64),
+            move || {
+                let app_id = get_running_app_id();
+
+                if app_id != previous {
+                    info!("Running app changed from {} to {}",previous,app_id);
+
+                    threadsafe_handler.call(RunningAppChange {
+                        app_id,
+                        previous_app_id: previous
+                    },ThreadsafeFunctionCallMode::NonBlocking);
+
+                    previous = app_id;
+                }
+
+                true
+            }
+        ))
+    }
+}
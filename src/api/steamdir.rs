@@ -0,0 +1,389 @@
+use napi_derive::napi;
+
+#[napi]
+pub mod steamdir {
+    use std::path::{Path,PathBuf};
+    use log::debug;
+    #[cfg(unix)]
+    use std::sync::{Arc,Mutex};
+    #[cfg(unix)]
+    use std::time::SystemTime;
+    #[cfg(unix)]
+    use crate::api::vdf::KeyValues;
+
+    #[napi(object)]
+    pub struct SteamRoot {
+        pub path: String,
+        /// Where the candidate was found, e.g. "native", "flatpak", "snap" or "registry"
+        pub source: String,
+        /// Whether the directory looks like a Steam install (has a "steamapps" folder)
+        pub valid: bool,
+        /// Whether a Steam client is currently running from this directory
+        pub running: bool,
+        /// The root used by the rest of the API: the running client's root, otherwise the default install
+        pub active: bool
+    }
+
+    struct Candidate {
+        path: PathBuf,
+        source: &'static str
+    }
+
+    #[cfg(unix)]
+    fn home_dir() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from)
+    }
+
+    #[cfg(target_os="linux")]
+    fn candidates() -> Vec<Candidate> {
+        let Some(home) = home_dir() else {
+            log::warn!("$HOME is not set, unable to locate Steam install");
+            return Vec::new()
+        };
+
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local/share"));
+
+        candidates_in(&home,&data_home)
+    }
+
+    #[cfg(target_os="linux")]
+    fn candidates_in(home: &Path,data_home: &Path) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam");
+        let snap = home.join("snap/steam/common");
+
+        // "~/.steam/root" and "~/.steam/steam" are symlinks maintained by the client, so they come first
+        for (path,source) in [
+            (home.join(".steam/root"),"native"),
+            (home.join(".steam/steam"),"native"),
+            (data_home.join("Steam"),"native"),
+            (home.join(".steam/debian-installation"),"native"),
+            (flatpak.join(".local/share/Steam"),"flatpak"),
+            (flatpak.join(".steam/steam"),"flatpak"),
+            (snap.join(".local/share/Steam"),"snap"),
+            (snap.join(".steam/steam"),"snap")
+        ] {
+            candidates.push(Candidate { path, source });
+        }
+
+        candidates
+    }
+
+    #[cfg(target_os="macos")]
+    fn candidates() -> Vec<Candidate> {
+        home_dir()
+            .map(|home| vec![Candidate {
+                path: home.join("Library/Application Support/Steam"),
+                source: "native"
+            }])
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_os="windows")]
+    fn candidates() -> Vec<Candidate> {
+        use crate::api::processes::win32::{RegKey,HKEY_CURRENT_USER,HKEY_LOCAL_MACHINE};
+
+        let mut candidates = Vec::new();
+
+        let registry_paths = [
+            (HKEY_CURRENT_USER,"Software\\Valve\\Steam","SteamPath"),
+            (HKEY_LOCAL_MACHINE,"SOFTWARE\\WOW6432Node\\Valve\\Steam","InstallPath"),
+            (HKEY_LOCAL_MACHINE,"SOFTWARE\\Valve\\Steam","InstallPath")
+        ];
+
+        for (hkey,subkey,value) in registry_paths {
+            match RegKey::predef(hkey).open_subkey(subkey).and_then(|key| key.get_value::<String,_>(value)) {
+                Ok(path) => candidates.push(Candidate {
+                    path: PathBuf::from(path.replace('/',"\\")),
+                    source: "registry"
+                }),
+                Err(err) => debug!("Unable to read \"{}\\{}\" from registry: {}",subkey,value,err)
+            }
+        }
+
+        if let Some(program_files) = std::env::var_os("ProgramFiles(x86)") {
+            candidates.push(Candidate {
+                path: PathBuf::from(program_files).join("Steam"),
+                source: "default"
+            });
+        }
+
+        candidates
+    }
+
+    /// Root directory of the currently running Steam client, if any
+    #[cfg(target_os="linux")]
+    fn running_root() -> Option<PathBuf> {
+        use process_alive::{state,State,Pid};
+
+        let home = home_dir()?;
+
+        [
+            home.join(".steam/steam.pid"),
+            home.join(".var/app/com.valvesoftware.Steam/.steam/steam.pid"),
+            home.join("snap/steam/common/.steam/steam.pid")
+        ].iter().find_map(|pidfile| {
+            let pid = std::fs::read_to_string(pidfile).ok()?.trim().parse::<u32>().ok()?;
+
+            if state(Pid::from(pid)) != State::Alive {
+                return None
+            }
+
+            // The client binary lives at "<root>/ubuntu12_32/steam"
+            let exe = std::fs::read_link(format!("/proc/{}/exe",pid)).ok()?;
+            exe.ancestors()
+                .find(|dir| dir.join("steamapps").is_dir())
+                .map(Path::to_path_buf)
+                .or_else(|| pidfile.parent().map(|dir| dir.join("steam")))
+        })
+    }
+
+    #[cfg(target_os="windows")]
+    fn running_root() -> Option<PathBuf> {
+        use crate::api::processes::win32::{RegKey,HKEY_CURRENT_USER};
+        use process_alive::{state,State,Pid};
+
+        let active = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey("Software\\Valve\\Steam\\ActiveProcess")
+            .ok()?;
+
+        let pid = active.get_value::<u32,_>("pid").ok()?;
+
+        if pid == 0 || state(Pid::from(pid)) != State::Alive {
+            return None
+        }
+
+        let dll = active.get_value::<String,_>("SteamClientDll").ok()?;
+        Path::new(&dll).parent().map(Path::to_path_buf)
+    }
+
+    #[cfg(target_os="macos")]
+    fn running_root() -> Option<PathBuf> {
+        None
+    }
+
+    fn canonical(path: &Path) -> Option<PathBuf> {
+        std::fs::canonicalize(path)
+            .ok()
+            .filter(|path| path.is_dir())
+    }
+
+    /// All Steam install directories found on this machine, deduplicated by their resolved path
+    pub(crate) fn find_steam_roots() -> Vec<SteamRoot> {
+        let roots = collect_roots(candidates(),running_root());
+        debug!("Found {} Steam root(s), active: {:?}",roots.len(),roots.iter().find(|root| root.active).map(|root| &root.path));
+        roots
+    }
+
+    fn collect_roots(candidates: Vec<Candidate>,running: Option<PathBuf>) -> Vec<SteamRoot> {
+        let running = running.and_then(|root| canonical(&root));
+        let mut seen: Vec<PathBuf> = Vec::new();
+        let mut roots = Vec::new();
+
+        for candidate in candidates {
+            let Some(path) = canonical(&candidate.path) else {
+                continue
+            };
+
+            if seen.contains(&path) {
+                continue
+            }
+
+            roots.push(SteamRoot {
+                path: path.to_string_lossy().to_string(),
+                source: candidate.source.to_string(),
+                valid: path.join("steamapps").is_dir(),
+                running: running.as_ref() == Some(&path),
+                active: false
+            });
+
+            seen.push(path);
+        }
+
+        let active = roots.iter().position(|root| root.running)
+            .or_else(|| roots.iter().position(|root| root.valid));
+
+        if let Some(index) = active {
+            roots[index].active = true;
+        }
+
+        roots
+    }
+
+    /// The active Steam root, used by the library/account APIs when no root is given explicitly
+    pub(crate) fn active_root() -> Option<PathBuf> {
+        find_steam_roots()
+            .into_iter()
+            .find(|root| root.active)
+            .map(|root| PathBuf::from(root.path))
+    }
+
+    /// Uses the root passed in from JS if there is one, falling back to the active root
+    pub(crate) fn resolve_root(steam_root: Option<String>) -> Option<PathBuf> {
+        let root = steam_root
+            .map(PathBuf::from)
+            .or_else(active_root);
+
+        if root.is_none() {
+            log::error!("No Steam install found");
+        }
+
+        root
+    }
+
+    /// registry.vdf's path, the mtime it was parsed at, and its contents
+    #[cfg(unix)]
+    type CachedRegistry = (PathBuf,SystemTime,Arc<KeyValues>);
+
+    #[cfg(unix)]
+    lazy_static! {
+        static ref REGISTRY: Mutex<Option<CachedRegistry>> = Mutex::new(None);
+    }
+
+    /// Steam's emulated registry on Linux/macOS and its mtime, picking the most recently written one if several installs exist
+    #[cfg(unix)]
+    fn registry_vdf(home: &Path) -> Option<(PathBuf,SystemTime)> {
+        [
+            home.join(".steam/registry.vdf"),
+            home.join(".var/app/com.valvesoftware.Steam/.steam/registry.vdf"),
+            home.join("snap/steam/common/.steam/registry.vdf"),
+            home.join("Library/Application Support/Steam/registry.vdf")
+        ].into_iter()
+            .filter_map(|path| {
+                let modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+                Some((path,modified))
+            })
+            .max_by_key(|(_,modified)| *modified)
+    }
+
+    /// The parsed registry.vdf, only read and parsed again once Steam has rewritten it
+    #[cfg(unix)]
+    fn registry() -> Option<Arc<KeyValues>> {
+        let (path,modified) = registry_vdf(&home_dir()?)?;
+        let mut cached = REGISTRY.lock().unwrap();
+
+        if let Some((cached_path,cached_modified,kv)) = cached.as_ref() {
+            if *cached_path == path && *cached_modified == modified {
+                return Some(kv.clone())
+            }
+        }
+
+        let kv = Arc::new(crate::api::vdf::read_file(&path)?);
+        *cached = Some((path,modified,kv.clone()));
+        Some(kv)
+    }
+
+    /// Reads a DWORD value under HKCU, e.g. `registry_dword("Software\\Valve\\Steam","RunningAppID")`
+    #[cfg(unix)]
+    pub(crate) fn registry_dword(key: &str,name: &str) -> Option<u32> {
+        dword(&*registry()?,key,name)
+    }
+
+    #[cfg(unix)]
+    fn dword(kv: &KeyValues,key: &str,name: &str) -> Option<u32> {
+        let path = ["Registry","HKCU"]
+            .into_iter()
+            .chain(key.split('\\'))
+            .chain([name])
+            .collect::<Vec<_>>();
+
+        let value = kv.path(&path)?.as_str()?;
+
+        match value.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex,16).ok(),
+            None => value.parse().ok()
+        }
+    }
+
+    #[cfg(target_os="windows")]
+    pub(crate) fn registry_dword(key: &str,name: &str) -> Option<u32> {
+        use crate::api::processes::win32::{RegKey,HKEY_CURRENT_USER};
+
+        RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey(key)
+            .and_then(|key| key.get_value::<u32,_>(name))
+            .ok()
+    }
+
+    #[napi]
+    pub fn get_steam_roots() -> Vec<SteamRoot> {
+        find_steam_roots()
+    }
+
+    #[napi]
+    pub fn get_steam_root() -> Option<String> {
+        active_root().map(|root| root.to_string_lossy().to_string())
+    }
+
+    #[cfg(all(test,target_os="linux"))]
+    mod tests {
+        use super::*;
+        use crate::api::testdir::TempDir;
+
+        #[test]
+        fn finds_roots_under_home() {
+            let home = TempDir::new("home-roots");
+            let native = home.join(".local/share/Steam");
+            let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+
+            std::fs::create_dir_all(native.join("steamapps")).unwrap();
+            std::fs::create_dir_all(&flatpak).unwrap();
+            std::fs::create_dir_all(home.join(".steam")).unwrap();
+            std::os::unix::fs::symlink(&native,home.join(".steam/root")).unwrap();
+            std::os::unix::fs::symlink(&native,home.join(".steam/steam")).unwrap();
+
+            let roots = collect_roots(candidates_in(&home,&home.join(".local/share")),None);
+            let summary = roots.iter().map(|root| (root.source.as_str(),root.valid,root.active)).collect::<Vec<_>>();
+
+            assert_eq!(summary,vec![("native",true,true),("flatpak",false,false)]);
+            assert_eq!(PathBuf::from(&roots[0].path),native.canonicalize().unwrap());
+
+            let roots = collect_roots(candidates_in(&home,&home.join(".local/share")),Some(flatpak.clone()));
+            assert!(roots[1].running && roots[1].active && !roots[0].active);
+        }
+
+        #[test]
+        fn uses_xdg_data_home() {
+            let home = TempDir::new("home-xdg");
+            let data_home = home.join("data");
+            std::fs::create_dir_all(data_home.join("Steam/steamapps")).unwrap();
+
+            let roots = collect_roots(candidates_in(&home,&data_home),None);
+
+            assert_eq!(roots.len(),1);
+            assert_eq!(PathBuf::from(&roots[0].path),data_home.join("Steam").canonicalize().unwrap());
+            assert!(roots[0].active);
+        }
+
+        #[test]
+        fn reads_newest_registry() {
+            let home = TempDir::new("home-registry");
+            let native = home.join(".steam/registry.vdf");
+            let flatpak = home.join(".var/app/com.valvesoftware.Steam/.steam/registry.vdf");
+
+            std::fs::create_dir_all(native.parent().unwrap()).unwrap();
+            std::fs::create_dir_all(flatpak.parent().unwrap()).unwrap();
+            std::fs::write(&native,"\"Registry\" {}").unwrap();
+            std::fs::write(&flatpak,r#""Registry" { "HKCU" { "Software" { "Valve" { "Steam" {
+                "RunningAppID" "0x1a"
+                "ActiveProcess" { "ActiveUser" "12345" }
+            } } } } }"#).unwrap();
+
+            let old = SystemTime::now() - std::time::Duration::from_secs(60);
+            std::fs::File::options().write(true).open(&native).unwrap().set_modified(old).unwrap();
+
+            let (path,_) = registry_vdf(&home).unwrap();
+            let kv = crate::api::vdf::read_file(&path).unwrap();
+
+            assert_eq!(path,flatpak);
+            assert_eq!(dword(&kv,"Software\\Valve\\Steam","RunningAppID"),Some(26));
+            assert_eq!(dword(&kv,"Software\\Valve\\Steam\\ActiveProcess","ActiveUser"),Some(12345));
+            assert_eq!(dword(&kv,"Software\\Valve\\Steam","Missing"),None);
+        }
+    }
+}
@@ -0,0 +1,824 @@
+use napi_derive::napi;
+use std::fmt;
+
+/// A value in a text VDF (KeyValues) document: either a string or a nested block.
+#[derive(Debug,Clone,PartialEq)]
+pub enum VdfValue {
+    String(String),
+    Object(KeyValues)
+}
+
+impl VdfValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(s) => Some(s),
+            VdfValue::Object(_) => None
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&KeyValues> {
+        match self {
+            VdfValue::String(_) => None,
+            VdfValue::Object(kv) => Some(kv)
+        }
+    }
+}
+
+/// Deepest block nesting either parser accepts. Real Steam files stay well below this, and each level is a
+/// stack frame, so hostile input is rejected instead of overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
+/// Ordered list of key/value pairs. Duplicate keys are kept, and lookups are case-insensitive like Steam's own parser.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct KeyValues(pub Vec<(String,VdfValue)>);
+
+impl KeyValues {
+    pub fn iter(&self) -> impl Iterator<Item = &(String,VdfValue)> {
+        self.0.iter()
+    }
+
+    pub fn get(&self,key: &str) -> Option<&VdfValue> {
+        self.0
+            .iter()
+            .find(|(k,_)| k.eq_ignore_ascii_case(key))
+            .map(|(_,v)| v)
+    }
+
+    pub fn get_all<'a>(&'a self,key: &'a str) -> impl Iterator<Item = &'a VdfValue> + 'a {
+        self.0
+            .iter()
+            .filter(move |(k,_)| k.eq_ignore_ascii_case(key))
+            .map(|(_,v)| v)
+    }
+
+    pub fn get_str(&self,key: &str) -> Option<&str> {
+        self.get(key).and_then(VdfValue::as_str)
+    }
+
+    pub fn get_object(&self,key: &str) -> Option<&KeyValues> {
+        self.get(key).and_then(VdfValue::as_object)
+    }
+
+    /// Follows a chain of nested keys, e.g. `["Registry","HKCU","Software"]`
+    pub fn path(&self,keys: &[&str]) -> Option<&VdfValue> {
+        let (last,parents) = keys.split_last()?;
+        let mut current = self;
+
+        for key in parents {
+            current = current.get_object(key)?;
+        }
+
+        current.get(last)
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct VdfError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+impl fmt::Display for VdfError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{} (line {}, column {})",self.message,self.line,self.column)
+    }
+}
+
+impl std::error::Error for VdfError {}
+
+#[derive(Debug,PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+    Condition(String),
+    Eof
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.trim_start_matches('\u{feff}').chars().peekable(),
+            line: 1,
+            column: 1
+        }
+    }
+
+    fn error(&self,message: impl Into<String>) -> VdfError {
+        VdfError {
+            line: self.line,
+            column: self.column,
+            message: message.into()
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                },
+                Some('/') => {
+                    let mut lookahead = self.chars.clone();
+                    lookahead.next();
+
+                    if lookahead.peek() != Some(&'/') {
+                        return
+                    }
+
+                    while let Some(c) = self.bump() {
+                        if c == '\n' {
+                            break
+                        }
+                    }
+                },
+                _ => return
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Token,VdfError> {
+        self.skip_trivia();
+
+        match self.chars.peek().copied() {
+            None => Ok(Token::Eof),
+            Some('{') => {
+                self.bump();
+                Ok(Token::Open)
+            },
+            Some('}') => {
+                self.bump();
+                Ok(Token::Close)
+            },
+            Some('"') => {
+                self.bump();
+                self.quoted()
+            },
+            Some('[') => {
+                self.bump();
+                let mut condition = String::new();
+
+                loop {
+                    match self.bump() {
+                        Some(']') => return Ok(Token::Condition(condition)),
+                        Some('\n') | None => return Err(self.error("Unterminated conditional")),
+                        Some(c) => condition.push(c)
+                    }
+                }
+            },
+            Some(_) => {
+                let mut value = String::new();
+
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || matches!(c,'{' | '}' | '"') {
+                        break
+                    }
+
+                    value.push(c);
+                    self.bump();
+                }
+
+                Ok(Token::Str(value))
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<Token,VdfError> {
+        let mut value = String::new();
+
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(Token::Str(value)),
+                Some('\\') => match self.bump() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('\\') => value.push('\\'),
+                    Some('"') => value.push('"'),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    },
+                    None => return Err(self.error("Unterminated string"))
+                },
+                Some(c) => value.push(c),
+                None => return Err(self.error("Unterminated string"))
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Token>
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Result<Token,VdfError> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.lexer.next_token()
+        }
+    }
+
+    fn peek(&mut self) -> Result<&Token,VdfError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lexer.next_token()?);
+        }
+
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    fn condition(&mut self) -> Result<bool,VdfError> {
+        if let Token::Condition(_) = self.peek()? {
+            if let Token::Condition(condition) = self.next()? {
+                return Ok(evaluate_condition(&condition))
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn block(&mut self,depth: usize) -> Result<KeyValues,VdfError> {
+        if depth > MAX_DEPTH {
+            return Err(self.lexer.error(format!("Blocks nested deeper than {}",MAX_DEPTH)))
+        }
+
+        let nested = depth > 0;
+        let mut kv = KeyValues::default();
+
+        loop {
+            let key = match self.next()? {
+                Token::Str(key) => key,
+                Token::Close if nested => return Ok(kv),
+                Token::Eof if !nested => return Ok(kv),
+                Token::Eof => return Err(self.lexer.error("Unexpected end of input, expected \"}\"")),
+                token => return Err(self.lexer.error(format!("Expected key, found {:?}",token)))
+            };
+
+            let mut include = self.condition()?;
+
+            let value = match self.next()? {
+                Token::Str(value) => VdfValue::String(value),
+                Token::Open => VdfValue::Object(self.block(depth + 1)?),
+                token => return Err(self.lexer.error(format!("Expected value for \"{}\", found {:?}",key,token)))
+            };
+
+            include &= self.condition()?;
+
+            if include {
+                kv.0.push((key,value));
+            }
+        }
+    }
+}
+
+/// Evaluates a `[$WIN32]`-style platform conditional against the current target
+fn evaluate_condition(condition: &str) -> bool {
+    let is_set = |name: &str| match name {
+        "WIN32" | "WINDOWS" => cfg!(target_os="windows"),
+        "WIN64" => cfg!(all(target_os="windows",target_pointer_width="64")),
+        "OSX" => cfg!(target_os="macos"),
+        "LINUX" => cfg!(target_os="linux"),
+        "POSIX" => cfg!(unix),
+        _ => false
+    };
+
+    condition.split("||").any(|group| {
+        group.split("&&").all(|term| {
+            let term = term.trim().trim_matches(|c| c == '(' || c == ')');
+
+            match term.strip_prefix('!') {
+                Some(negated) => !is_set(negated.trim_start_matches('$')),
+                None => is_set(term.trim_start_matches('$'))
+            }
+        })
+    })
+}
+
+/// Parses a text VDF document. Conditionals are evaluated for the current platform and entries that don't apply are dropped.
+pub fn parse(text: &str) -> Result<KeyValues,VdfError> {
+    Parser {
+        lexer: Lexer::new(text),
+        peeked: None
+    }.block(0)
+}
+
+/// Reads and parses a VDF file, logging (rather than returning) any IO or parse error
+pub(crate) fn read_file(path: &std::path::Path) -> Option<KeyValues> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| log::warn!("Unable to read \"{}\": {}",path.display(),err))
+        .ok()?;
+
+    parse(&text)
+        .map_err(|err| log::error!("Failed to parse \"{}\": {}",path.display(),err))
+        .ok()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\',"\\\\")
+        .replace('"',"\\\"")
+        .replace('\n',"\\n")
+        .replace('\t',"\\t")
+}
+
+fn write_block(out: &mut String,kv: &KeyValues,depth: usize) {
+    let indent = "\t".repeat(depth);
+
+    for (key,value) in kv.iter() {
+        match value {
+            VdfValue::String(s) => {
+                out.push_str(&format!("{}\"{}\"\t\t\"{}\"\n",indent,escape(key),escape(s)));
+            },
+            VdfValue::Object(child) => {
+                out.push_str(&format!("{}\"{}\"\n{}{{\n",indent,escape(key),indent));
+                write_block(out,child,depth + 1);
+                out.push_str(&format!("{}}}\n",indent));
+            }
+        }
+    }
+}
+
+/// Serialises key/values back into Steam's tab-indented text VDF format
+pub fn stringify(kv: &KeyValues) -> String {
+    let mut out = String::new();
+    write_block(&mut out,kv,0);
+    out
+}
+
+/// Converts to JSON. Keys that appear more than once, compared case-insensitively like lookups, become an array of
+/// their values under the first spelling.
+pub(crate) fn to_json(kv: &KeyValues) -> serde_json::Value {
+    use serde_json::{Map,Value};
+
+    let mut map = Map::new();
+
+    for (key,value) in kv.iter() {
+        let value = match value {
+            VdfValue::String(s) => Value::String(s.clone()),
+            VdfValue::Object(child) => to_json(child)
+        };
+
+        match map.iter_mut().find(|(k,_)| k.eq_ignore_ascii_case(key)).map(|(_,v)| v) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first,value]);
+            },
+            None => {
+                map.insert(key.clone(),value);
+            }
+        }
+    }
+
+    Value::Object(map)
+}
+
+/// Inverse of `to_json`: arrays expand into repeated keys, and numbers/booleans are written as strings
+pub(crate) fn from_json(value: &serde_json::Value) -> Result<KeyValues,String> {
+    use serde_json::Value;
+
+    fn push(kv: &mut KeyValues,key: &str,value: &Value) -> Result<(),String> {
+        match value {
+            Value::Null => {},
+            Value::String(s) => kv.0.push((key.to_string(),VdfValue::String(s.clone()))),
+            Value::Bool(b) => kv.0.push((key.to_string(),VdfValue::String((*b as u8).to_string()))),
+            Value::Number(n) => kv.0.push((key.to_string(),VdfValue::String(n.to_string()))),
+            Value::Object(_) => kv.0.push((key.to_string(),VdfValue::Object(from_json(value)?))),
+            Value::Array(values) => {
+                for value in values {
+                    if value.is_array() {
+                        return Err(format!("Nested arrays are not supported (key \"{}\")",key))
+                    }
+
+                    push(kv,key,value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    let map = value
+        .as_object()
+        .ok_or_else(|| "VDF root must be an object".to_string())?;
+
+    let mut kv = KeyValues::default();
+
+    for (key,value) in map {
+        push(&mut kv,key,value)?;
+    }
+
+    Ok(kv)
+}
+
+/// Error from a binary VDF, positioned by byte offset
+#[derive(Debug,Clone,PartialEq)]
+pub struct BinaryVdfError {
+    pub offset: usize,
+    pub message: String
+}
+
+impl fmt::Display for BinaryVdfError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{} (offset {})",self.message,self.offset)
+    }
+}
+
+impl std::error::Error for BinaryVdfError {}
+
+/// Little-endian cursor over binary VDF data
+pub(crate) struct BinaryReader<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> BinaryReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn error(&self,message: &str) -> BinaryVdfError {
+        BinaryVdfError {
+            offset: self.pos,
+            message: message.to_string()
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub(crate) fn take(&mut self,len: usize) -> Result<&'a [u8],BinaryVdfError> {
+        let end = self.pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| self.error("Unexpected end of data"))?;
+
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8,BinaryVdfError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32,BinaryVdfError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64,BinaryVdfError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Null-terminated UTF-8 (invalid sequences are replaced)
+    pub(crate) fn cstr(&mut self) -> Result<String,BinaryVdfError> {
+        let len = self.data[self.pos.min(self.data.len())..]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| self.error("Unterminated string"))?;
+
+        let text = String::from_utf8_lossy(self.take(len)?).to_string();
+        self.pos += 1;
+        Ok(text)
+    }
+
+    /// Null-terminated UTF-16LE
+    fn wstr(&mut self) -> Result<String,BinaryVdfError> {
+        let mut units = Vec::new();
+
+        loop {
+            match u16::from_le_bytes(self.take(2)?.try_into().unwrap()) {
+                0 => break,
+                unit => units.push(unit)
+            }
+        }
+
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+mod binary_type {
+    pub const OBJECT: u8 = 0x00;
+    pub const STRING: u8 = 0x01;
+    pub const INT32: u8 = 0x02;
+    pub const FLOAT32: u8 = 0x03;
+    pub const POINTER: u8 = 0x04;
+    pub const WSTRING: u8 = 0x05;
+    pub const COLOR: u8 = 0x06;
+    pub const UINT64: u8 = 0x07;
+    pub const END: u8 = 0x08;
+    pub const INT64: u8 = 0x0A;
+    pub const ALT_END: u8 = 0x0B;
+}
+
+fn binary_block(reader: &mut BinaryReader,strings: Option<&[String]>,depth: usize) -> Result<KeyValues,BinaryVdfError> {
+    use binary_type::*;
+
+    if depth > MAX_DEPTH {
+        return Err(reader.error(&format!("Objects nested deeper than {}",MAX_DEPTH)))
+    }
+
+    let nested = depth > 0;
+    let mut kv = KeyValues::default();
+
+    loop {
+        if !nested && reader.is_empty() {
+            return Ok(kv)
+        }
+
+        let kind = reader.u8()?;
+
+        if kind == END || kind == ALT_END {
+            return Ok(kv)
+        }
+
+        let key = match strings {
+            Some(strings) => {
+                let index = reader.u32()? as usize;
+                strings
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| reader.error(&format!("Key index {} is outside the string table",index)))?
+            },
+            None => reader.cstr()?
+        };
+
+        let value = match kind {
+            OBJECT => VdfValue::Object(binary_block(reader,strings,depth + 1)?),
+            STRING => VdfValue::String(reader.cstr()?),
+            INT32 | POINTER => VdfValue::String((reader.u32()? as i32).to_string()),
+            FLOAT32 => VdfValue::String(f32::from_bits(reader.u32()?).to_string()),
+            WSTRING => VdfValue::String(reader.wstr()?),
+            COLOR => VdfValue::String(reader.u32()?.to_string()),
+            UINT64 => VdfValue::String(reader.u64()?.to_string()),
+            INT64 => VdfValue::String((reader.u64()? as i64).to_string()),
+            _ => return Err(reader.error(&format!("Unknown value type 0x{:02x}",kind)))
+        };
+
+        kv.0.push((key,value));
+    }
+}
+
+/// Parses binary VDF, as used by appinfo.vdf and shortcuts.vdf. Numbers are converted to strings, matching text VDF.
+/// `strings` is the key table used by newer appinfo.vdf files, where keys are stored as indexes rather than inline.
+pub fn parse_binary(data: &[u8],strings: Option<&[String]>) -> Result<KeyValues,BinaryVdfError> {
+    binary_block(&mut BinaryReader::new(data),strings,0)
+}
+
+#[napi]
+pub mod vdf {
+    use napi::bindgen_prelude::{Buffer,Error};
+
+    #[napi]
+    pub fn parse(text: String) -> Result<serde_json::Value,Error> {
+        super::parse(&text)
+            .map(|kv| super::to_json(&kv))
+            .map_err(|err| Error::from_reason(format!("Failed to parse VDF: {}",err)))
+    }
+
+    #[napi]
+    pub fn stringify(obj: serde_json::Value) -> Result<String,Error> {
+        super::from_json(&obj)
+            .map(|kv| super::stringify(&kv))
+            .map_err(|err| Error::from_reason(format!("Failed to stringify VDF: {}",err)))
+    }
+
+    /// Parses a binary VDF file such as shortcuts.vdf
+    #[napi]
+    pub fn parse_binary(data: Buffer) -> Result<serde_json::Value,Error> {
+        super::parse_binary(&data,None)
+            .map(|kv| super::to_json(&kv))
+            .map_err(|err| Error::from_reason(format!("Failed to parse binary VDF: {}",err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(value: &str) -> VdfValue {
+        VdfValue::String(value.to_string())
+    }
+
+    #[test]
+    fn parses_nested_blocks() {
+        let kv = parse(r#"
+            "libraryfolders"
+            {
+                "0"
+                {
+                    "path"      "/home/user/.local/share/Steam"
+                    "apps"
+                    {
+                        "228980"    "359238614"
+                    }
+                }
+            }
+        "#).unwrap();
+
+        assert_eq!(kv.path(&["libraryfolders","0","path"]),Some(&s("/home/user/.local/share/Steam")));
+        assert_eq!(kv.path(&["libraryfolders","0","apps","228980"]),Some(&s("359238614")));
+    }
+
+    #[test]
+    fn lookups_are_case_insensitive() {
+        let kv = parse(r#""AppState" { "appid" "480" "SizeOnDisk" "1024" }"#).unwrap();
+
+        assert_eq!(kv.path(&["appstate","AppID"]).and_then(VdfValue::as_str),Some("480"));
+        assert_eq!(kv.get_object("APPSTATE").and_then(|app| app.get_str("sizeondisk")),Some("1024"));
+    }
+
+    #[test]
+    fn handles_escapes() {
+        let kv = parse(r#""path" "C:\\Program Files (x86)\\Steam" "quote" "say \"hi\"\n""#).unwrap();
+
+        assert_eq!(kv.get_str("path"),Some(r"C:\Program Files (x86)\Steam"));
+        assert_eq!(kv.get_str("quote"),Some("say \"hi\"\n"));
+    }
+
+    #[test]
+    fn keeps_unknown_escapes_verbatim() {
+        let kv = parse(r#""regex" "\d+""#).unwrap();
+        assert_eq!(kv.get_str("regex"),Some(r"\d+"));
+    }
+
+    #[test]
+    fn keeps_duplicate_keys() {
+        let kv = parse(r#""root" { "item" "a" "item" "b" "other" "c" }"#).unwrap();
+        let root = kv.get_object("root").unwrap();
+
+        assert_eq!(root.get_all("item").collect::<Vec<_>>(),vec![&s("a"),&s("b")]);
+        assert_eq!(root.get_str("item"),Some("a"));
+    }
+
+    #[test]
+    fn skips_comments_and_accepts_unquoted_tokens() {
+        let kv = parse("// header comment\nroot\n{\n\tkey value // trailing\n\tsub { a 1 }\n}\n").unwrap();
+
+        assert_eq!(kv.path(&["root","key"]),Some(&s("value")));
+        assert_eq!(kv.path(&["root","sub","a"]),Some(&s("1")));
+    }
+
+    #[test]
+    fn evaluates_conditionals() {
+        let kv = parse(r#"
+            "exe" "game.exe" [$WIN32]
+            "exe" "game.sh" [$LINUX]
+            "exe" "game.app" [$OSX]
+            "notwin" "1" [!$WIN32]
+            "either" "1" [$WIN32||$LINUX||$OSX]
+            "never" "1" [$X360]
+            "block" [$POSIX] { "a" "b" }
+        "#).unwrap();
+
+        let expected = if cfg!(target_os="windows") {
+            "game.exe"
+        } else if cfg!(target_os="macos") {
+            "game.app"
+        } else {
+            "game.sh"
+        };
+
+        assert_eq!(kv.get_all("exe").collect::<Vec<_>>(),vec![&s(expected)]);
+        assert_eq!(kv.get("notwin").is_some(),!cfg!(target_os="windows"));
+        assert!(kv.get("either").is_some());
+        assert!(kv.get("never").is_none());
+        assert_eq!(kv.get("block").is_some(),cfg!(unix));
+    }
+
+    #[test]
+    fn reports_errors_with_position() {
+        let err = parse("\"root\"\n{\n\t\"key\" \"value\"\n").unwrap_err();
+        assert_eq!(err.line,4);
+
+        let err = parse("\"root\" {\n\t\"key\" \"unterminated\n}").unwrap_err();
+        assert!(err.message.contains("Unterminated string"));
+
+        assert!(parse("\"key\" }").is_err());
+        assert!(parse("}").is_err());
+    }
+
+    #[test]
+    fn stringify_round_trips() {
+        let text = r#""root" { "path" "C:\\Steam" "nested" { "a" "1" "a" "2" } "quote" "\"x\"" }"#;
+        let kv = parse(text).unwrap();
+        let out = stringify(&kv);
+
+        assert!(out.starts_with("\"root\"\n{\n\t\"path\"\t\t\"C:\\\\Steam\"\n"));
+        assert_eq!(parse(&out).unwrap(),kv);
+    }
+
+    #[test]
+    fn json_conversion_groups_duplicates() {
+        let kv = parse(r#""root" { "item" "a" "item" "b" "child" { "x" "1" } }"#).unwrap();
+        let json = to_json(&kv);
+
+        assert_eq!(json,serde_json::json!({
+            "root": {
+                "item": ["a","b"],
+                "child": { "x": "1" }
+            }
+        }));
+
+        assert_eq!(from_json(&json).unwrap(),kv);
+    }
+
+    #[test]
+    fn json_conversion_groups_keys_case_insensitively() {
+        let kv = parse(r#""launch" { "0" "a" } "Launch" { "1" "b" } "LAUNCH" "c""#).unwrap();
+
+        assert_eq!(to_json(&kv),serde_json::json!({
+            "launch": [{ "0": "a" },{ "1": "b" },"c"]
+        }));
+    }
+
+    #[test]
+    fn json_scalars_become_strings() {
+        let kv = from_json(&serde_json::json!({ "n": 5, "b": true, "skip": null })).unwrap();
+
+        assert_eq!(kv.get_str("n"),Some("5"));
+        assert_eq!(kv.get_str("b"),Some("1"));
+        assert!(kv.get("skip").is_none());
+        assert!(from_json(&serde_json::json!(["x"])).is_err());
+    }
+
+    #[test]
+    fn parses_binary_values() {
+        let mut data = vec![0x00];
+        data.extend(b"shortcuts\0");
+        data.push(0x00);
+        data.extend(b"0\0");
+        data.push(0x01);
+        data.extend(b"AppName\0Game\0");
+        data.push(0x02);
+        data.extend(b"appid\0");
+        data.extend((-5i32).to_le_bytes());
+        data.push(0x07);
+        data.extend(b"big\0");
+        data.extend(u64::MAX.to_le_bytes());
+        data.extend([0x08,0x08,0x08]);
+
+        let kv = parse_binary(&data,None).unwrap();
+
+        assert_eq!(kv.path(&["shortcuts","0","AppName"]),Some(&s("Game")));
+        assert_eq!(kv.path(&["shortcuts","0","appid"]),Some(&s("-5")));
+        assert_eq!(kv.path(&["shortcuts","0","big"]),Some(&s("18446744073709551615")));
+    }
+
+    #[test]
+    fn parses_binary_with_string_table() {
+        let strings = vec!["common".to_string(),"name".to_string()];
+        let mut data = vec![0x00];
+        data.extend(0u32.to_le_bytes());
+        data.push(0x01);
+        data.extend(1u32.to_le_bytes());
+        data.extend(b"Half-Life\0");
+        data.push(0x08);
+
+        let kv = parse_binary(&data,Some(&strings)).unwrap();
+        assert_eq!(kv.path(&["common","name"]),Some(&s("Half-Life")));
+
+        let err = parse_binary(&[0x01,0x07,0x00,0x00,0x00],Some(&strings)).unwrap_err();
+        assert_eq!(err.offset,5);
+    }
+
+    #[test]
+    fn reports_truncated_binary() {
+        let err = parse_binary(&[0x00,b'a',0x00,0x02,b'b',0x00,0x01],None).unwrap_err();
+        assert_eq!(err.message,"Unexpected end of data");
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let text = format!("{}{}","\"a\" { ".repeat(100_000),"} ".repeat(100_000));
+        assert!(parse(&text).unwrap_err().message.contains("nested deeper"));
+
+        let data = [0x00,b'a',0x00].repeat(100_000);
+        assert!(parse_binary(&data,None).unwrap_err().message.contains("nested deeper"));
+
+        let text = format!("{}{}","\"a\" { ".repeat(MAX_DEPTH),"} ".repeat(MAX_DEPTH));
+        assert!(parse(&text).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+use napi_derive::napi;
+use std::sync::{Arc,Mutex};
+use std::sync::atomic::{AtomicBool,Ordering};
+use std::time::Duration;
+
+/// Shortest polling interval, so `intervalMs: 0` doesn't turn a watcher into a busy loop
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+type OnStop = Box<dyn FnOnce() + Send>;
+
+/// Returned by the `watch*` functions and `log.subscribe`. Call `stop()` to end the background thread or subscription.
+/// Polling intervals (`intervalMs`) below 100 ms are raised to 100 ms.
+#[napi]
+pub struct WatchHandle {
+    stopped: Arc<AtomicBool>,
+    /// Run by the first `stop()`, for work without a thread of its own to notice the flag
+    on_stop: Mutex<Option<OnStop>>
+}
+
+#[napi]
+impl WatchHandle {
+    #[napi]
+    pub fn stop(&self) {
+        self.stopped.store(true,Ordering::SeqCst);
+
+        if let Some(on_stop) = self.on_stop.lock().unwrap().take() {
+            on_stop();
+        }
+    }
+
+    #[napi(getter)]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+}
+
+impl WatchHandle {
+    /// A handle for work without a thread of its own, which `on_stop` tears down when the handle is stopped
+    pub(crate) fn on_stop(on_stop: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            stopped: Arc::new(AtomicBool::new(false)),
+            on_stop: Mutex::new(Some(Box::new(on_stop)))
+        }
+    }
+
+    /// Calls `tick` on a named background thread every `interval` (at least `MIN_INTERVAL`) until stopped, or until `tick` returns false
+    pub(crate) fn spawn<F>(name: &str,interval: Duration,mut tick: F) -> Self
+    where
+        F: FnMut() -> bool + Send + 'static
+    {
+        let interval = interval.max(MIN_INTERVAL);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let flag = stopped.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                while !flag.load(Ordering::SeqCst) {
+                    if !tick() {
+                        flag.store(true,Ordering::SeqCst);
+                        break
+                    }
+
+                    std::thread::sleep(interval);
+                }
+            });
+
+        if let Err(err) = spawned {
+            log::error!("Failed to spawn \"{}\" thread: {}",name,err);
+            stopped.store(true,Ordering::SeqCst);
+        }
+
+        Self { stopped, on_stop: Mutex::new(None) }
+    }
+}
@@ -0,0 +1,450 @@
+use std::io::{Read,Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path,PathBuf};
+use std::time::Duration;
+use serde_json::Value;
+use crate::api::wininfo::wininfo::Rect;
+
+const IPC_TIMEOUT: Duration = Duration::from_secs(1);
+/// i3/sway IPC message type for the layout tree
+const SWAY_GET_TREE: u32 = 4;
+const SWAY_MAGIC: &[u8] = b"i3-ipc";
+
+/// A window as reported by a Wayland compositor's IPC
+#[derive(Debug,Clone,PartialEq)]
+pub struct CompositorWindow {
+    pub id: i64,
+    pub pid: u32,
+    pub title: String,
+    /// Wayland app id, or the X11 class for XWayland windows
+    pub class: Option<String>,
+    /// Shown on a visible workspace and not hidden
+    pub visible: bool,
+    pub bounds: Rect,
+    pub focused: bool
+}
+
+/// "wayland", "x11" or "unknown", from `XDG_SESSION_TYPE` or, failing that, which display variables are set
+pub fn session_type() -> &'static str {
+    match std::env::var("XDG_SESSION_TYPE").ok().as_deref() {
+        Some("wayland") => return "wayland",
+        Some("x11") => return "x11",
+        _ => {}
+    }
+
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        "wayland"
+    } else if std::env::var_os("DISPLAY").is_some() {
+        "x11"
+    } else {
+        "unknown"
+    }
+}
+
+fn connect(path: &Path) -> Option<UnixStream> {
+    let stream = UnixStream::connect(path)
+        .map_err(|err| log::debug!("Unable to connect to \"{}\": {}",path.display(),err))
+        .ok()?;
+
+    stream.set_read_timeout(Some(IPC_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(IPC_TIMEOUT)).ok()?;
+    Some(stream)
+}
+
+/// Frames a message in sway's (i3's) IPC format: magic, payload length, message type, payload
+fn sway_message(kind: u32,payload: &[u8]) -> Vec<u8> {
+    let mut message = SWAY_MAGIC.to_vec();
+    message.extend((payload.len() as u32).to_ne_bytes());
+    message.extend(kind.to_ne_bytes());
+    message.extend(payload);
+    message
+}
+
+fn sway_request(path: &Path,kind: u32) -> Option<Value> {
+    sway_exchange(&mut connect(path)?,kind)
+}
+
+/// Sends one request over an open sway connection and reads its reply
+fn sway_exchange(stream: &mut UnixStream,kind: u32) -> Option<Value> {
+    stream.write_all(&sway_message(kind,b"")).ok()?;
+
+    let mut header = [0;14];
+    stream.read_exact(&mut header).ok()?;
+
+    if &header[..6] != SWAY_MAGIC {
+        log::error!("Unexpected reply from sway socket");
+        return None
+    }
+
+    let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+    let mut payload = vec![0;len];
+    stream.read_exact(&mut payload).ok()?;
+
+    serde_json::from_slice(&payload).ok()
+}
+
+/// Windows are the leaves of sway's layout tree that have a pid
+fn collect_sway_windows(node: &Value,windows: &mut Vec<CompositorWindow>) {
+    if let Some(pid) = node["pid"].as_u64() {
+        windows.push(CompositorWindow {
+            id: node["id"].as_i64().unwrap_or(0),
+            pid: pid as u32,
+            title: node["name"].as_str().unwrap_or("").to_string(),
+            class: node["app_id"].as_str().or(node["window_properties"]["class"].as_str()).map(String::from),
+            visible: node["visible"].as_bool().unwrap_or(false),
+            bounds: Rect {
+                x: node["rect"]["x"].as_i64().unwrap_or(0) as i32,
+                y: node["rect"]["y"].as_i64().unwrap_or(0) as i32,
+                width: node["rect"]["width"].as_u64().unwrap_or(0) as u32,
+                height: node["rect"]["height"].as_u64().unwrap_or(0) as u32
+            },
+            focused: node["focused"].as_bool().unwrap_or(false)
+        });
+    }
+
+    for key in ["nodes","floating_nodes"] {
+        for child in node[key].as_array().into_iter().flatten() {
+            collect_sway_windows(child,windows);
+        }
+    }
+}
+
+pub fn sway_windows_at(socket: &Path) -> Option<Vec<CompositorWindow>> {
+    let tree = sway_request(socket,SWAY_GET_TREE)?;
+    let mut windows = Vec::new();
+    collect_sway_windows(&tree,&mut windows);
+    Some(windows)
+}
+
+/// `None` unless running under sway (`SWAYSOCK` is set and answers)
+pub fn sway_windows() -> Option<Vec<CompositorWindow>> {
+    sway_windows_at(&PathBuf::from(std::env::var_os("SWAYSOCK")?))
+}
+
+fn hyprland_socket() -> Option<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+
+    // Hyprland 0.40 moved its sockets from /tmp to the runtime dir
+    let candidates = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr"))
+        .into_iter()
+        .chain(std::iter::once(PathBuf::from("/tmp/hypr")));
+
+    candidates
+        .map(|dir| dir.join(&signature).join(".socket.sock"))
+        .find(|socket| socket.exists())
+}
+
+fn hyprland_request(socket: &Path,command: &str) -> Option<Value> {
+    let mut stream = connect(socket)?;
+    stream.write_all(command.as_bytes()).ok()?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).ok()?;
+
+    serde_json::from_slice(&reply).ok()
+}
+
+fn parse_hyprland_clients(clients: &Value) -> Vec<CompositorWindow> {
+    clients
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|client| Some(CompositorWindow {
+            id: i64::from_str_radix(client["address"].as_str()?.trim_start_matches("0x"),16).unwrap_or(0),
+            pid: client["pid"].as_i64().filter(|pid| *pid > 0)? as u32,
+            title: client["title"].as_str().unwrap_or("").to_string(),
+            class: client["class"].as_str().filter(|class| !class.is_empty()).map(String::from),
+            visible: client["mapped"].as_bool().unwrap_or(true) && !client["hidden"].as_bool().unwrap_or(false),
+            bounds: Rect {
+                x: client["at"][0].as_i64().unwrap_or(0) as i32,
+                y: client["at"][1].as_i64().unwrap_or(0) as i32,
+                width: client["size"][0].as_u64().unwrap_or(0) as u32,
+                height: client["size"][1].as_u64().unwrap_or(0) as u32
+            },
+            focused: client["focusHistoryID"].as_i64() == Some(0)
+        }))
+        .collect()
+}
+
+pub fn hyprland_windows_at(socket: &Path) -> Option<Vec<CompositorWindow>> {
+    hyprland_request(socket,"j/clients").map(|clients| parse_hyprland_clients(&clients))
+}
+
+/// `None` unless running under Hyprland (`HYPRLAND_INSTANCE_SIGNATURE` is set and its socket answers)
+pub fn hyprland_windows() -> Option<Vec<CompositorWindow>> {
+    hyprland_windows_at(&hyprland_socket()?)
+}
+
+/// Windows from whichever compositor IPC answers, with the name of the backend
+pub fn compositor_windows() -> Option<(&'static str,Vec<CompositorWindow>)> {
+    sway_windows()
+        .map(|windows| ("sway",windows))
+        .or_else(|| hyprland_windows().map(|windows| ("hyprland",windows)))
+}
+
+fn hyprland_focused(socket: &Path) -> Option<Option<CompositorWindow>> {
+    Some(hyprland_windows_at(socket)?.into_iter().find(|window| window.focused))
+}
+
+/// Hyprland's event socket, which announces focus changes with "activewindow>>" lines
+pub struct HyprlandEvents {
+    stream: UnixStream,
+    pending: Vec<u8>
+}
+
+impl HyprlandEvents {
+    fn new(stream: UnixStream) -> Option<Self> {
+        stream.set_nonblocking(true).ok()?;
+        Some(Self { stream, pending: Vec::new() })
+    }
+
+    /// Whether focus changed since the last call, without blocking. `None` once Hyprland has closed the socket.
+    fn focus_changed(&mut self) -> Option<bool> {
+        let mut buffer = [0;4096];
+
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return None,
+                Ok(read) => self.pending.extend(&buffer[..read]),
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(_) => return None
+            }
+        }
+
+        let mut changed = false;
+
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            // Also covers "activewindowv2>>"
+            changed |= self.pending.starts_with(b"activewindow");
+            self.pending.drain(..=end);
+        }
+
+        Some(changed)
+    }
+}
+
+/// A compositor connection kept open across foreground checks, rather than reconnecting for each one
+pub enum CompositorSession {
+    /// sway answers any number of requests on one connection
+    Sway(UnixStream),
+    /// Hyprland's request socket takes one command per connection, so clients are only listed again after a focus event
+    Hyprland {
+        socket: PathBuf,
+        events: HyprlandEvents,
+        focused: Option<CompositorWindow>
+    }
+}
+
+impl CompositorSession {
+    /// `None` unless running under sway or Hyprland
+    pub fn connect() -> Option<Self> {
+        if let Some(stream) = std::env::var_os("SWAYSOCK").and_then(|path| connect(Path::new(&path))) {
+            return Some(CompositorSession::Sway(stream))
+        }
+
+        let socket = hyprland_socket()?;
+        let events = UnixStream::connect(socket.with_file_name(".socket2.sock"))
+            .map_err(|err| log::debug!("Unable to connect to Hyprland's event socket: {}",err))
+            .ok()
+            .and_then(HyprlandEvents::new)?;
+        let focused = hyprland_focused(&socket)?;
+
+        Some(CompositorSession::Hyprland { socket, events, focused })
+    }
+
+    pub fn backend(&self) -> &'static str {
+        match self {
+            CompositorSession::Sway(_) => "sway",
+            CompositorSession::Hyprland { .. } => "hyprland"
+        }
+    }
+
+    /// The focused window, if any. `None` once the compositor has gone away and the session needs reopening.
+    pub fn focused(&mut self) -> Option<Option<CompositorWindow>> {
+        match self {
+            CompositorSession::Sway(stream) => {
+                let mut windows = Vec::new();
+                collect_sway_windows(&sway_exchange(stream,SWAY_GET_TREE)?,&mut windows);
+                Some(windows.into_iter().find(|window| window.focused))
+            },
+            CompositorSession::Hyprland { socket, events, focused } => {
+                if events.focus_changed()? {
+                    *focused = hyprland_focused(socket)?;
+                }
+
+                Some(focused.clone())
+            }
+        }
+    }
+}
+
+/// Stand-in title when no window can be listed: the Windows exe for Wine/Proton games, otherwise the program name
+pub fn proc_title(pid: u32) -> Option<String> {
+    let process = crate::api::procfs::ProcFs::system().read(pid)?;
+
+    Some(match process.wine_exe() {
+        Some(exe) => exe.name,
+        None => process.cmdline
+            .first()
+            .and_then(|arg| Path::new(arg).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(process.comm)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::api::testdir::TempDir;
+
+    #[test]
+    fn walks_sway_tree() {
+        let tree = json!({
+            "id": 1, "type": "root", "name": "root", "pid": null,
+            "nodes": [{
+                "id": 3, "type": "workspace", "name": "1",
+                "nodes": [{
+                    "id": 7, "type": "con", "name": "Celeste", "pid": 4242, "focused": true, "visible": true,
+                    "app_id": null, "window_properties": { "class": "Celeste" },
+                    "rect": { "x": 0, "y": 0, "width": 1920, "height": 1080 }
+                }],
+                "floating_nodes": [{
+                    "id": 9, "type": "floating_con", "name": "Launcher", "pid": 4240, "focused": false, "visible": false,
+                    "app_id": "launcher", "rect": { "x": 100, "y": 50, "width": 640, "height": 480 }
+                }]
+            }]
+        });
+
+        let mut windows = Vec::new();
+        collect_sway_windows(&tree,&mut windows);
+
+        assert_eq!(windows,vec![
+            CompositorWindow {
+                id: 7,
+                pid: 4242,
+                title: "Celeste".to_string(),
+                class: Some("Celeste".to_string()),
+                visible: true,
+                bounds: Rect { x: 0, y: 0, width: 1920, height: 1080 },
+                focused: true
+            },
+            CompositorWindow {
+                id: 9,
+                pid: 4240,
+                title: "Launcher".to_string(),
+                class: Some("launcher".to_string()),
+                visible: false,
+                bounds: Rect { x: 100, y: 50, width: 640, height: 480 },
+                focused: false
+            }
+        ]);
+    }
+
+    #[test]
+    fn talks_sway_ipc() {
+        use std::os::unix::net::UnixListener;
+
+        let dir = TempDir::new("sway-ipc");
+        let socket = dir.join("sway.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream,_) = listener.accept().unwrap();
+            let mut request = [0;14];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(&request[..],&sway_message(SWAY_GET_TREE,b"")[..]);
+
+            let tree = json!({ "id": 1, "nodes": [{ "id": 2, "name": "Game", "pid": 10, "focused": true }] }).to_string();
+            stream.write_all(&sway_message(SWAY_GET_TREE,tree.as_bytes())).unwrap();
+        });
+
+        let windows = sway_windows_at(&socket).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(windows.len(),1);
+        assert_eq!((windows[0].pid,windows[0].title.as_str()),(10,"Game"));
+    }
+
+    #[test]
+    fn reuses_sway_connection() {
+        use std::os::unix::net::UnixListener;
+
+        let dir = TempDir::new("sway-session");
+        let socket = dir.join("sway.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        // Accepts a single connection, so a second connect would hang until the read timeout
+        let server = std::thread::spawn(move || {
+            let (mut stream,_) = listener.accept().unwrap();
+
+            for focused in [10,11] {
+                let mut request = [0;14];
+                stream.read_exact(&mut request).unwrap();
+
+                let tree = json!({ "id": 1, "nodes": [
+                    { "id": 2, "name": "Game", "pid": 10, "focused": focused == 10 },
+                    { "id": 3, "name": "Chat", "pid": 11, "focused": focused == 11 }
+                ] }).to_string();
+                stream.write_all(&sway_message(SWAY_GET_TREE,tree.as_bytes())).unwrap();
+            }
+        });
+
+        let mut session = CompositorSession::Sway(connect(&socket).unwrap());
+        let first = session.focused().unwrap().map(|window| window.pid);
+        let second = session.focused().unwrap().map(|window| window.pid);
+        server.join().unwrap();
+        let closed = session.focused();
+
+        assert_eq!((first,second),(Some(10),Some(11)));
+        assert!(closed.is_none());
+    }
+
+    #[test]
+    fn reads_hyprland_focus_events() {
+        let (mut hyprland,stream) = UnixStream::pair().unwrap();
+        let mut events = HyprlandEvents::new(stream).unwrap();
+
+        assert_eq!(events.focus_changed(),Some(false));
+
+        hyprland.write_all(b"workspace>>2\nopenwindow>>55d1c0,2,kitty,~\n").unwrap();
+        assert_eq!(events.focus_changed(),Some(false));
+
+        hyprland.write_all(b"activewindow>>kitty,~\nactivewin").unwrap();
+        assert_eq!(events.focus_changed(),Some(true));
+
+        hyprland.write_all(b"dowv2>>55d1c0\n").unwrap();
+        assert_eq!(events.focus_changed(),Some(true));
+        assert_eq!(events.focus_changed(),Some(false));
+
+        drop(hyprland);
+        assert_eq!(events.focus_changed(),None);
+    }
+
+    #[test]
+    fn parses_hyprland_clients() {
+        let clients = json!([
+            {
+                "address": "0x55d1c0", "pid": 777, "title": "Hades II", "class": "steam_app_1145350", "focusHistoryID": 0,
+                "mapped": true, "hidden": false, "at": [0,0], "size": [2560,1440]
+            },
+            { "address": "0x55d2c0", "pid": 778, "title": "Discord", "class": "discord", "focusHistoryID": 1, "hidden": true },
+            { "address": "0x55d3c0", "pid": -1, "title": "", "focusHistoryID": 2 }
+        ]);
+
+        let windows = parse_hyprland_clients(&clients);
+
+        assert_eq!(windows.len(),2);
+        assert_eq!(windows[0],CompositorWindow {
+            id: 0x55d1c0,
+            pid: 777,
+            title: "Hades II".to_string(),
+            class: Some("steam_app_1145350".to_string()),
+            visible: true,
+            bounds: Rect { x: 0, y: 0, width: 2560, height: 1440 },
+            focused: true
+        });
+        assert!(!windows[1].focused && !windows[1].visible);
+    }
+}
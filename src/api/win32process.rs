@@ -0,0 +1,161 @@
+use windows_sys::Wdk::System::Threading::{NtQueryInformationProcess,ProcessCommandLineInformation};
+use windows_sys::Win32::Foundation::{CloseHandle,FILETIME,HANDLE,INVALID_HANDLE_VALUE,UNICODE_STRING};
+use windows_sys::Win32::System::Diagnostics::ToolHelp::{CreateToolhelp32Snapshot,Process32FirstW,Process32NextW,PROCESSENTRY32W,TH32CS_SNAPPROCESS};
+use windows_sys::Win32::System::ProcessStatus::{K32GetProcessMemoryInfo,PROCESS_MEMORY_COUNTERS};
+use windows_sys::Win32::System::Threading::{GetProcessTimes,OpenProcess,QueryFullProcessImageNameW,PROCESS_NAME_WIN32,PROCESS_QUERY_LIMITED_INFORMATION};
+
+/// A process from a Toolhelp32 snapshot, plus what could be queried from the process itself
+#[derive(Debug,Clone,PartialEq)]
+pub struct Win32Process {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Exe file name, e.g. "Game.exe"
+    pub name: String,
+    /// Full exe path, unavailable for protected processes
+    pub exe: Option<String>,
+    pub cmdline: Option<String>,
+    /// Creation time as a FILETIME (100 ns intervals since 1601), or 0 if the process couldn't be opened
+    pub start_time: u64
+}
+
+/// CPU time, memory and threads of one process
+#[derive(Debug,Clone,PartialEq)]
+pub struct Win32Usage {
+    /// Kernel plus user time, in 100 ns intervals
+    pub cpu_time: u64,
+    /// Creation time as a FILETIME
+    pub start_time: u64,
+    pub working_set: u64,
+    pub threads: u32
+}
+
+/// Closes the handle on drop
+struct Handle(HANDLE);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+fn wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+fn image_path(process: HANDLE) -> Option<String> {
+    let mut buffer = vec![0u16;32768];
+    let mut len = buffer.len() as u32;
+
+    (unsafe { QueryFullProcessImageNameW(process,PROCESS_NAME_WIN32,buffer.as_mut_ptr(),&mut len) } != 0)
+        .then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+fn open(pid: u32) -> Option<Handle> {
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION,0,pid) };
+    (handle != 0).then_some(Handle(handle))
+}
+
+fn filetime(time: FILETIME) -> u64 {
+    (time.dwHighDateTime as u64) << 32 | time.dwLowDateTime as u64
+}
+
+/// Creation, kernel and user time
+fn times(process: HANDLE) -> Option<(u64,u64,u64)> {
+    let zero = FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+    let (mut created,mut exited,mut kernel,mut user) = (zero,zero,zero,zero);
+
+    (unsafe { GetProcessTimes(process,&mut created,&mut exited,&mut kernel,&mut user) } != 0)
+        .then(|| (filetime(created),filetime(kernel),filetime(user)))
+}
+
+fn working_set(process: HANDLE) -> Option<u64> {
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+
+    (unsafe { K32GetProcessMemoryInfo(process,&mut counters,counters.cb) } != 0)
+        .then_some(counters.WorkingSetSize as u64)
+}
+
+/// `ProcessCommandLineInformation` (Windows 8.1+) only needs limited query access, unlike reading the PEB
+fn command_line(process: HANDLE) -> Option<String> {
+    let mut len = 0;
+    unsafe { NtQueryInformationProcess(process,ProcessCommandLineInformation,std::ptr::null_mut(),0,&mut len) };
+
+    if len == 0 {
+        return None
+    }
+
+    // u64s keep the UNICODE_STRING header at the start of the buffer aligned
+    let mut buffer = vec![0u64;(len as usize).div_ceil(8)];
+    let status = unsafe { NtQueryInformationProcess(process,ProcessCommandLineInformation,buffer.as_mut_ptr().cast(),len,&mut len) };
+
+    if status < 0 {
+        return None
+    }
+
+    let string = unsafe { &*buffer.as_ptr().cast::<UNICODE_STRING>() };
+
+    if string.Buffer.is_null() {
+        return Some(String::new())
+    }
+
+    let chars = unsafe { std::slice::from_raw_parts(string.Buffer,string.Length as usize / 2) };
+    Some(String::from_utf16_lossy(chars))
+}
+
+fn process(entry: &PROCESSENTRY32W) -> Win32Process {
+    let handle = open(entry.th32ProcessID);
+
+    Win32Process {
+        pid: entry.th32ProcessID,
+        ppid: entry.th32ParentProcessID,
+        name: wide(&entry.szExeFile),
+        exe: handle.as_ref().and_then(|handle| image_path(handle.0)),
+        cmdline: handle.as_ref().and_then(|handle| command_line(handle.0)),
+        start_time: handle.as_ref().and_then(|handle| times(handle.0)).map_or(0,|(created,_,_)| created)
+    }
+}
+
+/// The raw entries of one Toolhelp32 snapshot
+fn snapshot() -> Vec<PROCESSENTRY32W> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS,0) };
+
+    if snapshot == INVALID_HANDLE_VALUE {
+        log::error!("Failed to snapshot processes: {}",std::io::Error::last_os_error());
+        return Vec::new()
+    }
+
+    let snapshot = Handle(snapshot);
+    let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+    let mut entries = Vec::new();
+    let mut found = unsafe { Process32FirstW(snapshot.0,&mut entry) } != 0;
+
+    while found {
+        entries.push(entry);
+        found = unsafe { Process32NextW(snapshot.0,&mut entry) } != 0;
+    }
+
+    entries
+}
+
+/// Every running process, from a single Toolhelp32 snapshot
+pub fn processes() -> Vec<Win32Process> {
+    snapshot().iter().map(process).collect()
+}
+
+/// Usage of `pid`. Only that process is opened, unlike `processes()` which queries every entry.
+pub fn usage(pid: u32) -> Option<Win32Usage> {
+    let threads = snapshot().into_iter().find(|entry| entry.th32ProcessID == pid)?.cntThreads;
+    let handle = open(pid)?;
+    let (start_time,kernel,user) = times(handle.0)?;
+
+    Some(Win32Usage {
+        cpu_time: kernel + user,
+        start_time,
+        working_set: working_set(handle.0).unwrap_or(0),
+        threads
+    })
+}
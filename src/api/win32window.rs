@@ -0,0 +1,118 @@
+use windows_sys::Win32::Foundation::{BOOL,HWND,LPARAM,RECT};
+use windows_sys::Win32::Graphics::Gdi::{EnumDisplayMonitors,GetMonitorInfoW,HDC,HMONITOR,MONITORINFO,MONITORINFOEXW};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    EnumWindows,GetClassNameW,GetForegroundWindow,GetWindowLongW,GetWindowRect,GetWindowTextLengthW,GetWindowTextW,
+    GetWindowThreadProcessId,IsIconic,IsWindowVisible,GWL_STYLE,MONITORINFOF_PRIMARY,WS_CAPTION,WS_THICKFRAME
+};
+use crate::api::wininfo::wininfo::Rect;
+
+/// A display from `EnumDisplayMonitors`
+#[derive(Debug,Clone,PartialEq)]
+pub struct Win32Monitor {
+    /// Device name, e.g. "\\.\DISPLAY1"
+    pub name: String,
+    pub bounds: Rect,
+    pub primary: bool
+}
+
+fn rect(rect: &RECT) -> Rect {
+    Rect {
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left).max(0) as u32,
+        height: (rect.bottom - rect.top).max(0) as u32
+    }
+}
+
+unsafe extern "system" fn collect_window(hwnd: HWND,lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam as *mut Vec<HWND>);
+    windows.push(hwnd);
+    1
+}
+
+/// Every top-level window, in z-order
+pub fn top_level_windows() -> Vec<HWND> {
+    let mut windows: Vec<HWND> = Vec::new();
+    unsafe { EnumWindows(Some(collect_window),&mut windows as *mut Vec<HWND> as LPARAM) };
+    windows
+}
+
+pub fn window_pid(hwnd: HWND) -> u32 {
+    let mut pid = 0;
+    unsafe { GetWindowThreadProcessId(hwnd,&mut pid) };
+    pid
+}
+
+pub fn title(hwnd: HWND) -> String {
+    let len = unsafe { GetWindowTextLengthW(hwnd) };
+
+    if len <= 0 {
+        return String::new()
+    }
+
+    let mut buffer = vec![0u16;len as usize + 1];
+    let copied = unsafe { GetWindowTextW(hwnd,buffer.as_mut_ptr(),buffer.len() as i32) };
+    String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+}
+
+/// Registered window class, e.g. "UnityWndClass" or "UnrealWindow"
+pub fn class_name(hwnd: HWND) -> Option<String> {
+    let mut buffer = [0u16;256];
+    let copied = unsafe { GetClassNameW(hwnd,buffer.as_mut_ptr(),buffer.len() as i32) };
+    (copied > 0).then(|| String::from_utf16_lossy(&buffer[..copied as usize]))
+}
+
+pub fn bounds(hwnd: HWND) -> Option<Rect> {
+    let mut bounds = RECT { left: 0, top: 0, right: 0, bottom: 0 };
+    (unsafe { GetWindowRect(hwnd,&mut bounds) } != 0).then(|| rect(&bounds))
+}
+
+pub fn is_visible(hwnd: HWND) -> bool {
+    unsafe { IsWindowVisible(hwnd) != 0 }
+}
+
+pub fn is_minimized(hwnd: HWND) -> bool {
+    unsafe { IsIconic(hwnd) != 0 }
+}
+
+pub fn is_focused(hwnd: HWND) -> bool {
+    unsafe { GetForegroundWindow() == hwnd }
+}
+
+/// Has a title bar or a resizable frame
+pub fn is_decorated(hwnd: HWND) -> bool {
+    let style = unsafe { GetWindowLongW(hwnd,GWL_STYLE) } as u32;
+    style & WS_CAPTION == WS_CAPTION || style & WS_THICKFRAME != 0
+}
+
+/// Whether the foreground app is running in Direct3D exclusive fullscreen
+pub fn is_exclusive_fullscreen() -> bool {
+    use windows_sys::Win32::UI::Shell::{SHQueryUserNotificationState,QUNS_RUNNING_D3D_FULL_SCREEN};
+
+    let mut state = 0;
+    unsafe { SHQueryUserNotificationState(&mut state) == 0 && state == QUNS_RUNNING_D3D_FULL_SCREEN }
+}
+
+unsafe extern "system" fn collect_monitor(monitor: HMONITOR,_hdc: HDC,_rect: *mut RECT,lparam: LPARAM) -> BOOL {
+    let monitors = &mut *(lparam as *mut Vec<Win32Monitor>);
+    let mut info: MONITORINFOEXW = std::mem::zeroed();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+
+    if GetMonitorInfoW(monitor,&mut info as *mut MONITORINFOEXW as *mut MONITORINFO) != 0 {
+        let len = info.szDevice.iter().position(|c| *c == 0).unwrap_or(info.szDevice.len());
+
+        monitors.push(Win32Monitor {
+            name: String::from_utf16_lossy(&info.szDevice[..len]),
+            bounds: rect(&info.monitorInfo.rcMonitor),
+            primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0
+        });
+    }
+
+    1
+}
+
+pub fn monitors() -> Vec<Win32Monitor> {
+    let mut monitors: Vec<Win32Monitor> = Vec::new();
+    unsafe { EnumDisplayMonitors(0,std::ptr::null(),Some(collect_monitor),&mut monitors as *mut Vec<Win32Monitor> as LPARAM) };
+    monitors
+}
@@ -1,53 +1,671 @@
+use napi_derive::napi;
+
+#[napi]
 pub mod wininfo {
-    #[allow(unused)]
-    pub fn window_title_from_pid(pid: u32) -> Option<String> {
-        use std::process::Command;
-        
-        #[cfg(target_os="windows")] {
-            use std::os::windows::process::CommandExt;
-            const CREATENOWINDOW: u32 = 0x08000000;
-
-            let cmd = format!("Get-Process | where {{ $_.Id -eq {} }} | select -ExpandProperty MainWindowTitle",pid);
-
-            let output = Command::new("powershell")
-                .creation_flags(CREATENOWINDOW)
-                .args(["-Command",&cmd])
-                .output()
-                .expect("Failed to run process list command");
-
-            let windowtitle= String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-            if !windowtitle.is_empty() {
-                return Some(windowtitle)
-            }
-
-            None
-        }
-        
-        #[cfg(target_os="linux")] {
-            let output = Command::new("wmctrl")
-                .arg("-lp")
-                .output()
-                .expect("Failed to get output from \"wmctrl\"");
-        
-            let stdout = String::from_utf8_lossy(&output.stdout);
-        
-            for line in stdout.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-        
-                if parts.len() < 5 {
-                    continue;
+    use log::info;
+    #[cfg(not(target_os="windows"))]
+    use log::error;
+    use napi::JsFunction;
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{ThreadsafeFunction,ThreadsafeFunctionCallMode,ErrorStrategy};
+    use std::time::Duration;
+    use crate::api::watcher::WatchHandle;
+
+    const DEFAULT_INTERVAL_MS: u32 = 500;
+
+    #[napi(object)]
+    #[derive(Debug,Clone,PartialEq)]
+    pub struct WindowInfo {
+        /// X11 window id, HWND on Windows, or the compositor's id for sway/Hyprland windows
+        pub id: i64,
+        pub pid: u32,
+        pub title: String,
+        /// What answered: "win32", "x11", "wmctrl", "sway", "hyprland", or "proc" when no window could be listed
+        /// and the title was taken from the process' command line instead
+        pub backend: String
+    }
+
+    #[napi(object)]
+    #[derive(Debug,Clone,Copy,PartialEq)]
+    pub struct Rect {
+        pub x: i32,
+        pub y: i32,
+        pub width: u32,
+        pub height: u32
+    }
+
+    impl Rect {
+        fn right(&self) -> i64 {
+            self.x as i64 + self.width as i64
+        }
+
+        fn bottom(&self) -> i64 {
+            self.y as i64 + self.height as i64
+        }
+
+        pub(crate) fn overlap(&self,other: &Rect) -> u64 {
+            let width = self.right().min(other.right()) - (self.x.max(other.x) as i64);
+            let height = self.bottom().min(other.bottom()) - (self.y.max(other.y) as i64);
+            (width.max(0) * height.max(0)) as u64
+        }
+
+        pub(crate) fn covers(&self,other: &Rect) -> bool {
+            self.x <= other.x && self.y <= other.y && self.right() >= other.right() && self.bottom() >= other.bottom()
+        }
+    }
+
+    #[napi(object)]
+    pub struct Monitor {
+        pub index: u32,
+        /// e.g. "DP-1" on X11 or "\\.\DISPLAY1" on Windows
+        pub name: String,
+        pub bounds: Rect,
+        pub primary: bool
+    }
+
+    #[napi(object)]
+    pub struct WindowState {
+        pub id: i64,
+        pub pid: u32,
+        pub title: String,
+        /// Screen coordinates of the window, excluding the window manager's frame on X11
+        pub bounds: Rect,
+        /// Index into `getMonitors()` of the monitor showing most of the window
+        pub monitor: Option<u32>,
+        /// Covers its whole monitor, whether exclusive or borderless
+        pub fullscreen: bool,
+        /// Direct3D exclusive fullscreen, where overlays can't be drawn on top. Always false on Linux.
+        pub exclusive_fullscreen: bool,
+        /// Has no title bar or frame
+        pub borderless: bool,
+        pub minimized: bool,
+        pub focused: bool
+    }
+
+    #[napi(object)]
+    pub struct ForegroundChange {
+        /// The newly focused window, missing when nothing has focus
+        pub window: Option<WindowInfo>,
+        pub previous: Option<WindowInfo>
+    }
+
+    #[napi(object)]
+    #[derive(Debug,Clone,PartialEq)]
+    pub struct WindowDetails {
+        pub id: i64,
+        pub pid: u32,
+        pub title: String,
+        /// Class half of `WM_CLASS` on X11, the app id on Wayland or the window class name on Windows
+        pub class: Option<String>,
+        /// Mapped on X11, shown on a visible workspace on Wayland, `IsWindowVisible` on Windows
+        pub visible: bool,
+        pub minimized: bool,
+        /// Missing when the backend doesn't report geometry ("wmctrl" and "proc")
+        pub bounds: Option<Rect>,
+        /// Set on the one window most likely to be the game's: titled, visible and not minimized, then the largest
+        pub main: bool,
+        /// Same values as `WindowInfo.backend`
+        pub backend: String
+    }
+
+    impl From<WindowDetails> for WindowInfo {
+        fn from(window: WindowDetails) -> Self {
+            WindowInfo {
+                id: window.id,
+                pid: window.pid,
+                title: window.title,
+                backend: window.backend
+            }
+        }
+    }
+
+    /// Marks the main window, ranked by having a title, then visibility, then area. Untitled windows are mostly
+    /// splash screens and helpers, while a launcher and the game it starts are both titled, and the game's window
+    /// is nearly always the larger.
+    fn pick_main(windows: &mut [WindowDetails]) {
+        let main = windows
+            .iter()
+            .enumerate()
+            .max_by_key(|(_,window)| (
+                !window.title.is_empty(),
+                window.visible && !window.minimized,
+                window.bounds.map_or(0,|bounds| bounds.width as u64 * bounds.height as u64)
+            ))
+            .map(|(index,_)| index);
+
+        if let Some(index) = main {
+            windows[index].main = true;
+        }
+    }
+
+    /// The monitor showing most of `bounds`
+    fn monitor_index(monitors: &[Rect],bounds: &Rect) -> Option<u32> {
+        monitors
+            .iter()
+            .enumerate()
+            .map(|(index,monitor)| (index,monitor.overlap(bounds)))
+            .filter(|(_,overlap)| *overlap > 0)
+            .max_by_key(|(_,overlap)| *overlap)
+            .map(|(index,_)| index as u32)
+    }
+
+    #[cfg(target_os="windows")]
+    fn find_windows(pid: u32) -> Vec<WindowInfo> {
+        list_windows(&[pid]).into_iter().map(WindowInfo::from).collect()
+    }
+
+    /// Hidden windows without a title (IME, GDI+ and other helpers) are left out
+    #[cfg(target_os="windows")]
+    fn list_windows(pids: &[u32]) -> Vec<WindowDetails> {
+        use crate::api::win32window as win32;
+
+        win32::top_level_windows()
+            .into_iter()
+            .filter_map(|hwnd| {
+                let pid = win32::window_pid(hwnd);
+                let title = win32::title(hwnd);
+                let visible = win32::is_visible(hwnd);
+
+                (pids.contains(&pid) && (visible || !title.is_empty())).then(|| WindowDetails {
+                    id: hwnd as i64,
+                    pid,
+                    title,
+                    class: win32::class_name(hwnd),
+                    visible,
+                    minimized: win32::is_minimized(hwnd),
+                    bounds: win32::bounds(hwnd),
+                    main: false,
+                    backend: "win32".to_string()
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(target_os="linux")]
+    fn x11_window(window: crate::api::x11::X11Window,backend: &str) -> WindowInfo {
+        WindowInfo {
+            id: window.id as i64,
+            pid: window.pid,
+            title: window.title,
+            backend: backend.to_string()
+        }
+    }
+
+    #[cfg(target_os="linux")]
+    fn compositor_window(window: crate::api::wayland::CompositorWindow,backend: &str) -> WindowInfo {
+        WindowInfo {
+            id: window.id,
+            pid: window.pid,
+            title: window.title,
+            backend: backend.to_string()
+        }
+    }
+
+    /// Asks the X server directly, falling back to `wmctrl` if that isn't possible. On Wayland, XWayland only
+    /// knows about X11 clients, so native windows are looked up over the compositor's IPC (sway or Hyprland),
+    /// and as a last resort the process' command line stands in for the title.
+    #[cfg(target_os="linux")]
+    fn list_windows(pids: &[u32]) -> Vec<WindowDetails> {
+        use crate::api::wayland::{session_type,compositor_windows,proc_title};
+        use crate::api::x11::{X11,wmctrl_windows};
+
+        let wayland = session_type() == "wayland";
+
+        if let Some(x11) = X11::connect() {
+            let windows = x11
+                .windows()
+                .into_iter()
+                .filter(|window| pids.contains(&window.pid))
+                .map(|window| {
+                    let state = x11.window_state(window.id);
+
+                    WindowDetails {
+                        id: window.id as i64,
+                        pid: window.pid,
+                        title: window.title,
+                        class: x11.window_class(window.id),
+                        visible: state.as_ref().is_some_and(|state| state.viewable),
+                        minimized: state.as_ref().is_some_and(|state| state.minimized),
+                        bounds: state.map(|state| state.bounds),
+                        main: false,
+                        backend: "x11".to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if !wayland || !windows.is_empty() {
+                return windows
+            }
+        }
+
+        if wayland {
+            if let Some((backend,windows)) = compositor_windows() {
+                return windows
+                    .into_iter()
+                    .filter(|window| pids.contains(&window.pid))
+                    .map(|window| WindowDetails {
+                        id: window.id,
+                        pid: window.pid,
+                        title: window.title,
+                        class: window.class,
+                        visible: window.visible,
+                        minimized: false,
+                        bounds: Some(window.bounds),
+                        main: false,
+                        backend: backend.to_string()
+                    })
+                    .collect()
+            }
+
+            info!("No compositor IPC available on this Wayland session, using the command lines of {:?}",pids);
+
+            return pids
+                .iter()
+                .filter_map(|pid| Some(WindowDetails {
+                    id: 0,
+                    pid: *pid,
+                    title: proc_title(*pid)?,
+                    class: None,
+                    visible: false,
+                    minimized: false,
+                    bounds: None,
+                    main: false,
+                    backend: "proc".to_string()
+                }))
+                .collect()
+        }
+
+        match wmctrl_windows() {
+            // wmctrl only lists windows the window manager is showing
+            Some(windows) => windows
+                .into_iter()
+                .filter(|window| pids.contains(&window.pid))
+                .map(|window| WindowDetails {
+                    id: window.id as i64,
+                    pid: window.pid,
+                    title: window.title,
+                    class: None,
+                    visible: true,
+                    minimized: false,
+                    bounds: None,
+                    main: false,
+                    backend: "wmctrl".to_string()
+                })
+                .collect(),
+            None => {
+                error!("Unable to list windows: no X display available and \"wmctrl\" failed");
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(target_os="linux")]
+    fn find_windows(pid: u32) -> Vec<WindowInfo> {
+        list_windows(&[pid]).into_iter().map(WindowInfo::from).collect()
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn list_windows(_pids: &[u32]) -> Vec<WindowDetails> {
+        error!("Window lookup is not supported on this platform");
+        Vec::new()
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn find_windows(_pid: u32) -> Vec<WindowInfo> {
+        error!("Window lookup is not supported on this platform");
+        Vec::new()
+    }
+
+    #[cfg(target_os="windows")]
+    fn find_monitors() -> Vec<Monitor> {
+        crate::api::win32window::monitors()
+            .into_iter()
+            .enumerate()
+            .map(|(index,monitor)| Monitor {
+                index: index as u32,
+                name: monitor.name,
+                bounds: monitor.bounds,
+                primary: monitor.primary
+            })
+            .collect()
+    }
+
+    #[cfg(target_os="windows")]
+    fn find_window_state(pid: u32) -> Option<WindowState> {
+        use crate::api::win32window as win32;
+        use windows_sys::Win32::Foundation::HWND;
+
+        let main = tree_windows(&[pid]).into_iter().find(|window| window.main)?;
+        let hwnd = main.id as HWND;
+        let bounds = main.bounds?;
+        let monitors = find_monitors().into_iter().map(|monitor| monitor.bounds).collect::<Vec<_>>();
+        let monitor = monitor_index(&monitors,&bounds);
+        let fullscreen = monitor.is_some_and(|index| bounds.covers(&monitors[index as usize]));
+        let focused = win32::is_focused(hwnd);
+
+        Some(WindowState {
+            id: main.id,
+            pid,
+            title: main.title,
+            bounds,
+            monitor,
+            fullscreen,
+            exclusive_fullscreen: focused && fullscreen && win32::is_exclusive_fullscreen(),
+            borderless: !win32::is_decorated(hwnd),
+            minimized: main.minimized,
+            focused
+        })
+    }
+
+    #[cfg(target_os="linux")]
+    fn find_monitors() -> Vec<Monitor> {
+        use crate::api::x11::X11;
+
+        X11::connect()
+            .map(|x11| x11.monitors())
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(index,monitor)| Monitor {
+                index: index as u32,
+                name: monitor.name,
+                bounds: monitor.bounds,
+                primary: monitor.primary
+            })
+            .collect()
+    }
+
+    #[cfg(target_os="linux")]
+    fn find_window_state(pid: u32) -> Option<WindowState> {
+        use crate::api::x11::X11;
+
+        // Only X11 reports the state below, so a main window found over compositor IPC is no use here
+        let main = tree_windows(&[pid]).into_iter().find(|window| window.main && window.backend == "x11")?;
+        let x11 = X11::connect()?;
+        let state = x11.window_state(main.id as u32)?;
+        let monitors = x11.monitors().into_iter().map(|monitor| monitor.bounds).collect::<Vec<_>>();
+
+        let monitor = monitor_index(&monitors,&state.bounds);
+        let covers = monitor.is_some_and(|index| state.bounds.covers(&monitors[index as usize]));
+
+        Some(WindowState {
+            id: main.id,
+            pid,
+            title: main.title,
+            bounds: state.bounds,
+            monitor,
+            fullscreen: state.fullscreen || covers,
+            exclusive_fullscreen: false,
+            borderless: state.fullscreen || state.decorated == Some(false),
+            minimized: state.minimized,
+            focused: state.focused
+        })
+    }
+
+    #[cfg(target_os="windows")]
+    fn find_foreground() -> Option<WindowInfo> {
+        use crate::api::win32window as win32;
+        use windows_sys::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+        let hwnd = unsafe { GetForegroundWindow() };
+
+        (hwnd != 0).then(|| WindowInfo {
+            id: hwnd as i64,
+            pid: win32::window_pid(hwnd),
+            title: win32::title(hwnd),
+            backend: "win32".to_string()
+        })
+    }
+
+    /// Connections reused across foreground checks, so a watcher doesn't reconnect on every tick.
+    /// Each is dropped and reopened if it stops answering.
+    #[cfg(target_os="linux")]
+    #[derive(Default)]
+    struct ForegroundSource {
+        compositor: Option<crate::api::wayland::CompositorSession>,
+        x11: Option<crate::api::x11::X11>
+    }
+
+    #[cfg(target_os="linux")]
+    impl ForegroundSource {
+        /// On Wayland the compositor knows about every window, while XWayland only sees X11 clients, so it's asked first
+        fn foreground(&mut self) -> Option<WindowInfo> {
+            use crate::api::wayland::{session_type,CompositorSession};
+            use crate::api::x11::X11;
+
+            if session_type() == "wayland" {
+                if self.compositor.is_none() {
+                    self.compositor = CompositorSession::connect();
                 }
-        
-                if let Ok(line_pid) = parts[2].parse::<u32>() {
-                    if line_pid == pid {
-                        let window = parts[4..].join(" ");
-                        return Some(window);
+
+                if let Some(session) = self.compositor.as_mut() {
+                    match session.focused() {
+                        Some(window) => return window.map(|window| compositor_window(window,session.backend())),
+                        None => self.compositor = None
                     }
                 }
             }
-        
-            None
+
+            if self.x11.is_none() {
+                self.x11 = X11::connect();
+            }
+
+            let x11 = self.x11.as_ref()?;
+            let window = x11.active_window();
+
+            if window.is_none() && !x11.is_connected() {
+                self.x11 = None;
+            }
+
+            window.map(|window| x11_window(window,"x11"))
+        }
+    }
+
+    #[cfg(not(target_os="linux"))]
+    #[derive(Default)]
+    struct ForegroundSource {}
+
+    #[cfg(not(target_os="linux"))]
+    impl ForegroundSource {
+        fn foreground(&mut self) -> Option<WindowInfo> {
+            find_foreground()
+        }
+    }
+
+    #[cfg(target_os="linux")]
+    fn find_foreground() -> Option<WindowInfo> {
+        ForegroundSource::default().foreground()
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn find_foreground() -> Option<WindowInfo> {
+        None
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn find_monitors() -> Vec<Monitor> {
+        Vec::new()
+    }
+
+    #[cfg(not(any(target_os="windows",target_os="linux")))]
+    fn find_window_state(_pid: u32) -> Option<WindowState> {
+        None
+    }
+
+    /// `pids`' windows with the main one marked
+    fn tree_windows(pids: &[u32]) -> Vec<WindowDetails> {
+        let mut windows = list_windows(pids);
+        pick_main(&mut windows);
+        windows
+    }
+
+    /// Title of `pid`'s main window, so a launcher or splash window doesn't win just by being listed first
+    pub fn window_title_from_pid(pid: u32) -> Option<String> {
+        tree_windows(&[pid])
+            .into_iter()
+            .find(|window| window.main)
+            .map(|window| window.title)
+            .filter(|title| !title.is_empty())
+    }
+
+    /// "wayland", "x11", "windows" or "unknown"
+    #[napi]
+    pub fn get_session_type() -> String {
+        #[cfg(target_os="linux")]
+        return crate::api::wayland::session_type().to_string();
+
+        #[cfg(target_os="windows")]
+        return "windows".to_string();
+
+        #[cfg(not(any(target_os="windows",target_os="linux")))]
+        return "unknown".to_string();
+    }
+
+    /// Every top-level window owned by `pid`
+    #[napi]
+    pub fn get_windows(pid: u32) -> Vec<WindowInfo> {
+        find_windows(pid)
+    }
+
+    /// Every window owned by `pid` or its descendants, with the likely game window marked `main`
+    #[napi]
+    pub fn get_tree_windows(pid: u32) -> Vec<WindowDetails> {
+        use crate::api::processes::processes::get_process_tree;
+
+        let pids = get_process_tree(pid).iter().map(|process| process.pid).collect::<Vec<_>>();
+        tree_windows(&pids)
+    }
+
+    /// Like `getTreeWindows`, for every process launched for `appid`
+    #[napi]
+    pub fn get_app_windows(appid: u32) -> Vec<WindowDetails> {
+        use crate::api::processes::processes::get_app_process_tree;
+
+        let pids = get_app_process_tree(appid).iter().map(|process| process.pid).collect::<Vec<_>>();
+        tree_windows(&pids)
+    }
+
+    #[napi]
+    pub fn get_monitors() -> Vec<Monitor> {
+        find_monitors()
+    }
+
+    /// Geometry, monitor and fullscreen/focus state of `pid`'s main window, the one `WindowDetails.main` marks, for placing notifications
+    #[napi]
+    pub fn get_window_state(pid: u32) -> Option<WindowState> {
+        find_window_state(pid)
+    }
+
+    /// The window that currently has focus. `pid` is 0 if the window doesn't say which process owns it.
+    #[napi]
+    pub fn get_foreground_window() -> Option<WindowInfo> {
+        find_foreground()
+    }
+
+    /// Calls `handler` whenever focus moves to a different window, including once on start
+    #[napi]
+    pub fn watch_foreground(
+        #[napi(ts_arg_type = "(change: ForegroundChange) => void")] handler: JsFunction,
+        interval_ms: Option<u32>
+    ) -> Result<WatchHandle,Error> {
+        let threadsafe_handler: ThreadsafeFunction<ForegroundChange,ErrorStrategy::Fatal> = handler
+            .create_threadsafe_function(0,|ctx| Ok(vec![ctx.value]))?;
+
+        let mut source = ForegroundSource::default();
+        let mut previous: Option<WindowInfo> = None;
+        let mut started = false;
+
+        Ok(WatchHandle::spawn(
+            "foreground-watcher",
+            Duration::from_millis(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS) as u64),
+            move || {
+                let window = source.foreground();
+                let key = |window: &Option<WindowInfo>| window.as_ref().map(|window| (window.id,window.pid));
+
+                if !started || key(&window) != key(&previous) {
+                    info!("Foreground window changed to {:?}",window.as_ref().map(|window| (window.pid,&window.title)));
+
+                    threadsafe_handler.call(ForegroundChange {
+                        window: window.clone(),
+                        previous: previous.take()
+                    },ThreadsafeFunctionCallMode::NonBlocking);
+
+                    started = true;
+                }
+
+                previous = window;
+                true
+            }
+        ))
+    }
+
+    /// Whether the focused window belongs to one of the processes launched for `appid`
+    #[napi]
+    pub fn is_app_focused(appid: u32) -> bool {
+        use crate::api::processes::processes::get_app_process_tree;
+
+        let Some(window) = find_foreground().filter(|window| window.pid != 0) else {
+            return false
+        };
+
+        get_app_process_tree(appid).iter().any(|process| process.pid == window.pid)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn rect(x: i32,y: i32,width: u32,height: u32) -> Rect {
+            Rect { x, y, width, height }
+        }
+
+        #[test]
+        fn picks_monitor_with_most_overlap() {
+            let monitors = [rect(0,0,1920,1080),rect(1920,0,2560,1440)];
+
+            assert_eq!(monitor_index(&monitors,&rect(100,100,800,600)),Some(0));
+            assert_eq!(monitor_index(&monitors,&rect(1800,0,800,600)),Some(1));
+            assert_eq!(monitor_index(&monitors,&rect(-900,-900,800,600)),None);
+            assert!(rect(1920,0,2560,1440).covers(&monitors[1]));
+            assert!(!rect(1920,30,2560,1410).covers(&monitors[1]));
+        }
+
+        fn window(id: i64,title: &str,visible: bool,bounds: Option<Rect>) -> WindowDetails {
+            WindowDetails {
+                id,
+                pid: 1,
+                title: title.to_string(),
+                class: None,
+                visible,
+                minimized: false,
+                bounds,
+                main: false,
+                backend: "x11".to_string()
+            }
+        }
+
+        #[test]
+        fn picks_largest_titled_visible_window() {
+            let mut windows = vec![
+                window(1,"Launcher",true,Some(rect(0,0,800,600))),
+                window(2,"",true,Some(rect(0,0,3840,2160))),
+                window(3,"Game",true,Some(rect(0,0,1920,1080))),
+                window(4,"Hidden",false,Some(rect(0,0,3840,2160)))
+            ];
+
+            pick_main(&mut windows);
+
+            assert_eq!(windows.iter().filter(|window| window.main).map(|window| window.id).collect::<Vec<_>>(),vec![3]);
+
+            let mut splash = vec![window(5,"",true,Some(rect(0,0,640,360))),window(6,"Game",false,None)];
+            pick_main(&mut splash);
+            assert!(!splash[0].main && splash[1].main);
+
+            let mut hidden = vec![window(7,"",false,None)];
+            pick_main(&mut hidden);
+            assert!(hidden[0].main);
+
+            pick_main(&mut []);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,390 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum,ConnectionExt,MapState,Window};
+use x11rb::rust_connection::RustConnection;
+use crate::api::wininfo::wininfo::Rect;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_CLIENT_LIST,
+        _NET_WM_PID,
+        _NET_WM_NAME,
+        _NET_WM_STATE,
+        _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_STATE_HIDDEN,
+        _NET_ACTIVE_WINDOW,
+        _NET_FRAME_EXTENTS,
+        _MOTIF_WM_HINTS,
+        WM_STATE,
+        UTF8_STRING,
+    }
+}
+
+/// `WM_STATE` value for minimised windows
+const ICONIC_STATE: u32 = 3;
+/// `_MOTIF_WM_HINTS` flag saying the decorations field is set
+const MWM_HINTS_DECORATIONS: u32 = 2;
+
+/// A top-level window as reported by the X server
+#[derive(Debug,Clone,PartialEq)]
+pub struct X11Window {
+    pub id: u32,
+    pub pid: u32,
+    pub title: String
+}
+
+/// Window manager state of a window
+#[derive(Debug,Clone,PartialEq)]
+pub struct X11WindowState {
+    /// Position on the root window, excluding the window manager's frame
+    pub bounds: Rect,
+    /// The window manager has put the window in fullscreen mode
+    pub fullscreen: bool,
+    /// Whether the window has a title bar and border, if the window manager or client says
+    pub decorated: Option<bool>,
+    pub minimized: bool,
+    pub focused: bool,
+    pub viewable: bool
+}
+
+/// A monitor from RandR, or the whole screen if RandR isn't available
+#[derive(Debug,Clone,PartialEq)]
+pub struct X11Monitor {
+    pub name: String,
+    pub bounds: Rect,
+    pub primary: bool
+}
+
+/// Connection to the X server (or XWayland) named by `DISPLAY`
+pub struct X11 {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms
+}
+
+impl X11 {
+    /// `None` when there is no display to connect to, e.g. on a headless system or a Wayland session without XWayland
+    pub fn connect() -> Option<Self> {
+        let (conn,screen) = x11rb::connect(None)
+            .map_err(|err| log::debug!("Unable to connect to X server: {}",err))
+            .ok()?;
+
+        let root = conn.setup().roots.get(screen)?.root;
+        let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+
+        Some(Self { conn, root, atoms })
+    }
+
+    fn property32(&self,window: Window,property: u32,kind: impl Into<u32>) -> Vec<u32> {
+        self.conn
+            .get_property(false,window,property,kind,0,u32::MAX / 4)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|values| values.collect()))
+            .unwrap_or_default()
+    }
+
+    fn property_string(&self,window: Window,property: u32,kind: impl Into<u32>) -> Option<String> {
+        let reply = self.conn
+            .get_property(false,window,property,kind,0,u32::MAX / 4)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        (reply.format == 8 && !reply.value.is_empty()).then(|| String::from_utf8_lossy(&reply.value).to_string())
+    }
+
+    /// Managed windows from the window manager's `_NET_CLIENT_LIST`. Without a window manager (e.g. bare Xvfb)
+    /// the root's direct children are used instead.
+    pub fn client_windows(&self) -> Vec<Window> {
+        let clients = self.property32(self.root,self.atoms._NET_CLIENT_LIST,AtomEnum::WINDOW);
+
+        if !clients.is_empty() {
+            return clients
+        }
+
+        self.conn
+            .query_tree(self.root)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|tree| tree.children)
+            .unwrap_or_default()
+    }
+
+    pub fn window_pid(&self,window: Window) -> Option<u32> {
+        self.property32(window,self.atoms._NET_WM_PID,AtomEnum::CARDINAL).first().copied()
+    }
+
+    /// `_NET_WM_NAME`, falling back to the legacy `WM_NAME`
+    pub fn window_title(&self,window: Window) -> Option<String> {
+        self.property_string(window,self.atoms._NET_WM_NAME,self.atoms.UTF8_STRING)
+            .or_else(|| self.property_string(window,AtomEnum::WM_NAME.into(),AtomEnum::ANY))
+    }
+
+    pub fn windows(&self) -> Vec<X11Window> {
+        self.client_windows()
+            .into_iter()
+            .filter_map(|id| Some(X11Window {
+                id,
+                pid: self.window_pid(id)?,
+                title: self.window_title(id).unwrap_or_default()
+            }))
+            .collect()
+    }
+
+    pub fn windows_for_pid(&self,pid: u32) -> Vec<X11Window> {
+        self.windows().into_iter().filter(|window| window.pid == pid).collect()
+    }
+
+    /// Class half of `WM_CLASS`, e.g. "steam_app_1145360"
+    pub fn window_class(&self,window: Window) -> Option<String> {
+        let reply = self.conn
+            .get_property(false,window,AtomEnum::WM_CLASS,AtomEnum::STRING,0,u32::MAX / 4)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        parse_wm_class(&reply.value)
+    }
+
+    fn bounds(&self,window: Window) -> Option<Rect> {
+        let geometry = self.conn.get_geometry(window).ok()?.reply().ok()?;
+        let origin = self.conn.translate_coordinates(window,self.root,0,0).ok()?.reply().ok()?;
+
+        Some(Rect {
+            x: origin.dst_x as i32,
+            y: origin.dst_y as i32,
+            width: geometry.width as u32,
+            height: geometry.height as u32
+        })
+    }
+
+    fn decorated(&self,window: Window) -> Option<bool> {
+        let hints = self.property32(window,self.atoms._MOTIF_WM_HINTS,self.atoms._MOTIF_WM_HINTS);
+
+        if let (Some(flags),Some(decorations)) = (hints.first(),hints.get(2)) {
+            if flags & MWM_HINTS_DECORATIONS != 0 {
+                return Some(*decorations != 0)
+            }
+        }
+
+        let extents = self.property32(window,self.atoms._NET_FRAME_EXTENTS,AtomEnum::CARDINAL);
+        (extents.len() == 4).then(|| extents.iter().any(|extent| *extent != 0))
+    }
+
+    /// Whether the server still answers, to tell "no active window" apart from a lost connection
+    pub fn is_connected(&self) -> bool {
+        self.conn
+            .get_input_focus()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some()
+    }
+
+    /// The window the window manager reports as active, or the input focus without one
+    pub fn active_window(&self) -> Option<X11Window> {
+        let active = match self.property32(self.root,self.atoms._NET_ACTIVE_WINDOW,AtomEnum::WINDOW).first() {
+            Some(active) => *active,
+            None => self.conn.get_input_focus().ok()?.reply().ok()?.focus
+        };
+
+        // 0 is "no window", and 1 is PointerRoot for the input focus
+        if active <= 1 || active == self.root {
+            return None
+        }
+
+        Some(X11Window {
+            id: active,
+            pid: self.window_pid(active).unwrap_or(0),
+            title: self.window_title(active).unwrap_or_default()
+        })
+    }
+
+    fn focused(&self,window: Window) -> bool {
+        match self.property32(self.root,self.atoms._NET_ACTIVE_WINDOW,AtomEnum::WINDOW).first() {
+            Some(active) => *active == window,
+            None => self.conn
+                .get_input_focus()
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .is_some_and(|focus| focus.focus == window)
+        }
+    }
+
+    pub fn window_state(&self,window: Window) -> Option<X11WindowState> {
+        let bounds = self.bounds(window)?;
+        let states = self.property32(window,self.atoms._NET_WM_STATE,AtomEnum::ATOM);
+        let wm_state = self.property32(window,self.atoms.WM_STATE,self.atoms.WM_STATE);
+
+        let viewable = self.conn
+            .get_window_attributes(window)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some_and(|attributes| attributes.map_state == MapState::VIEWABLE);
+
+        Some(X11WindowState {
+            bounds,
+            fullscreen: states.contains(&self.atoms._NET_WM_STATE_FULLSCREEN),
+            decorated: self.decorated(window),
+            minimized: states.contains(&self.atoms._NET_WM_STATE_HIDDEN) || wm_state.first() == Some(&ICONIC_STATE),
+            focused: self.focused(window),
+            viewable
+        })
+    }
+
+    pub fn monitors(&self) -> Vec<X11Monitor> {
+        use x11rb::protocol::randr::ConnectionExt as _;
+
+        let monitors = self.conn
+            .randr_get_monitors(self.root,true)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.monitors)
+            .unwrap_or_default();
+
+        if monitors.is_empty() {
+            let screen = self.conn.setup().roots.iter().find(|screen| screen.root == self.root);
+
+            return screen
+                .map(|screen| vec![X11Monitor {
+                    name: "screen".to_string(),
+                    bounds: Rect {
+                        x: 0,
+                        y: 0,
+                        width: screen.width_in_pixels as u32,
+                        height: screen.height_in_pixels as u32
+                    },
+                    primary: true
+                }])
+                .unwrap_or_default()
+        }
+
+        monitors
+            .into_iter()
+            .map(|monitor| X11Monitor {
+                name: self.conn
+                    .get_atom_name(monitor.name)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .map(|reply| String::from_utf8_lossy(&reply.name).to_string())
+                    .unwrap_or_default(),
+                bounds: Rect {
+                    x: monitor.x as i32,
+                    y: monitor.y as i32,
+                    width: monitor.width as u32,
+                    height: monitor.height as u32
+                },
+                primary: monitor.primary
+            })
+            .collect()
+    }
+}
+
+/// `WM_CLASS` holds two null-terminated strings, the instance name then the class name
+fn parse_wm_class(value: &[u8]) -> Option<String> {
+    let mut parts = value.split(|byte| *byte == 0);
+    let instance = parts.next()?;
+    let class = parts.next().filter(|class| !class.is_empty()).unwrap_or(instance);
+
+    (!class.is_empty()).then(|| String::from_utf8_lossy(class).to_string())
+}
+
+/// Parses a line of `wmctrl -lp` ("<id> <desktop> <pid> <host> <title>"), keeping the title's own spacing
+pub fn parse_wmctrl_line(line: &str) -> Option<X11Window> {
+    let mut rest = line;
+    let mut fields = Vec::new();
+
+    for _ in 0..4 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    Some(X11Window {
+        id: u32::from_str_radix(fields[0].trim_start_matches("0x"),16).ok()?,
+        pid: fields[2].parse().ok()?,
+        title: rest.strip_prefix(' ').unwrap_or(rest).to_string()
+    })
+}
+
+/// Window list from the `wmctrl` tool, for setups where connecting to the X server directly fails.
+/// `None` if wmctrl isn't installed or fails.
+pub fn wmctrl_windows() -> Option<Vec<X11Window>> {
+    let output = std::process::Command::new("wmctrl")
+        .arg("-lp")
+        .output()
+        .map_err(|err| log::debug!("Unable to run \"wmctrl\": {}",err))
+        .ok()?;
+
+    if !output.status.success() {
+        log::debug!("\"wmctrl\" failed: {}",String::from_utf8_lossy(&output.stderr).trim());
+        return None
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_wmctrl_line).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wmctrl_lines() {
+        let window = parse_wmctrl_line("0x03a00003  0 2342   myhost Hollow  Knight - v1.5").unwrap();
+
+        assert_eq!(window.id,0x03a00003);
+        assert_eq!(window.pid,2342);
+        assert_eq!(window.title,"Hollow  Knight - v1.5");
+
+        assert_eq!(parse_wmctrl_line("0x01000007 -1 0      N/A ").unwrap().title,"");
+        assert!(parse_wmctrl_line("0x01000007 -1").is_none());
+    }
+
+    #[test]
+    fn parses_wm_class() {
+        assert_eq!(parse_wm_class(b"steam_app_413150\0steam_app_413150\0").as_deref(),Some("steam_app_413150"));
+        assert_eq!(parse_wm_class(b"navigator\0Firefox\0").as_deref(),Some("Firefox"));
+        assert_eq!(parse_wm_class(b"solo\0").as_deref(),Some("solo"));
+        assert_eq!(parse_wm_class(b""),None);
+    }
+
+    #[test]
+    #[ignore = "needs an X display, e.g. xvfb-run cargo test -- --ignored"]
+    fn finds_own_window_on_display() {
+        use x11rb::protocol::xproto::{CreateWindowAux,PropMode,WindowClass};
+        use x11rb::wrapper::ConnectionExt as _;
+
+        let x11 = X11::connect().expect("no X display");
+
+        let window = x11.conn.generate_id().unwrap();
+        x11.conn.create_window(0,window,x11.root,0,0,64,64,0,WindowClass::INPUT_OUTPUT,0,&CreateWindowAux::new()).unwrap();
+        x11.conn.change_property32(PropMode::REPLACE,window,x11.atoms._NET_WM_PID,AtomEnum::CARDINAL,&[std::process::id()]).unwrap();
+        x11.conn.change_property8(PropMode::REPLACE,window,x11.atoms._NET_WM_NAME,x11.atoms.UTF8_STRING,"steamworks  test".as_bytes()).unwrap();
+        x11.conn.change_property8(PropMode::REPLACE,window,AtomEnum::WM_CLASS,AtomEnum::STRING,b"test\0SteamworksTest\0").unwrap();
+        x11.conn.map_window(window).unwrap();
+        x11.conn.sync().unwrap();
+
+        // A window manager lists the window in _NET_CLIENT_LIST once it has managed it, which isn't instant
+        let mut windows = Vec::new();
+
+        for _ in 0..20 {
+            windows = x11.windows_for_pid(std::process::id());
+
+            if windows.iter().any(|found| found.id == window) {
+                break
+            }
+
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+
+        let state = x11.window_state(window).unwrap();
+        let class = x11.window_class(window);
+        x11.conn.destroy_window(window).unwrap();
+        x11.conn.flush().unwrap();
+
+        assert!(windows.iter().any(|found| found.id == window && found.title == "steamworks  test"));
+        assert!(state.bounds.width > 0 && state.bounds.height > 0);
+        assert!(!state.minimized);
+        assert_eq!(class.as_deref(),Some("SteamworksTest"));
+    }
+}
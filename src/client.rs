@@ -1,10 +1,25 @@
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool,AtomicU32,Ordering};
 use steamworks::Client;
 
 lazy_static! {
     static ref STEAM_CLIENT: Mutex<Option<Client>> = Mutex::new(None);
 }
 
+// Mirrors of the client's state that can be read without locking, e.g. from the panic hook
+static INITIALISED: AtomicBool = AtomicBool::new(false);
+static APP_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Whether `init` has succeeded, without taking the client lock
+pub fn is_initialised() -> bool {
+    INITIALISED.load(Ordering::SeqCst)
+}
+
+/// App id the client was initialised with, without taking the client lock
+pub fn app_id() -> Option<u32> {
+    Some(APP_ID.load(Ordering::SeqCst)).filter(|app_id| *app_id != 0)
+}
+
 pub fn has_client() -> bool {
     STEAM_CLIENT.lock().unwrap().is_some()
 }
@@ -15,11 +30,17 @@ pub fn get_client() -> Client {
 }
 
 pub fn set_client(client: Client) {
+    APP_ID.store(client.utils().app_id().0,Ordering::SeqCst);
+    INITIALISED.store(true,Ordering::SeqCst);
+
     let mut client_ref = STEAM_CLIENT.lock().unwrap();
     *client_ref = Some(client);
 }
 
 pub fn drop_client() {
+    INITIALISED.store(false,Ordering::SeqCst);
+    APP_ID.store(0,Ordering::SeqCst);
+
     let mut client_ref = STEAM_CLIENT.lock().unwrap();
     *client_ref = None;
 }